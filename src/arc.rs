@@ -0,0 +1,345 @@
+use crate::{
+    order, CancellationToken, ConditionKey, Instant, Monitor, MonitorGuard, RawMutex,
+    RawMutexHandoff, SignalMode, WaitOutcome,
+};
+use alloc::sync::Arc;
+use core::{
+    marker::PhantomData,
+    mem::ManuallyDrop,
+    ops::{Deref, DerefMut},
+    ptr,
+    time::Duration,
+};
+use lock_api::RawMutexFair;
+
+pub struct ArcMonitorGuard<T, C = usize, R: lock_api::RawMutex = RawMutex> {
+    monitor: Arc<Monitor<T, C, R>>,
+    mode: SignalMode,
+    _guard: PhantomData<*const ()>,
+}
+
+// Sendable exactly when the raw mutex's own guards are.
+unsafe impl<T: Send, C, R: lock_api::RawMutex + Send + Sync> Send for ArcMonitorGuard<T, C, R> where
+    R::GuardMarker: Send
+{
+}
+
+unsafe impl<T: Sync, C, R: lock_api::RawMutex + Sync> Sync for ArcMonitorGuard<T, C, R> where
+    R::GuardMarker: Sync
+{
+}
+
+impl<T, C: ConditionKey, R: lock_api::RawMutex> ArcMonitorGuard<T, C, R> {
+    pub(crate) fn new(monitor: Arc<Monitor<T, C, R>>) -> Self {
+        ArcMonitorGuard {
+            monitor,
+            mode: SignalMode::Continue,
+            _guard: PhantomData,
+        }
+    }
+
+    // Everything but releasing the lock is delegated to a borrowed guard that
+    // is never dropped, so the lock stays with this one.
+    fn guard(&mut self) -> ManuallyDrop<MonitorGuard<'_, T, C, R>> {
+        ManuallyDrop::new(MonitorGuard {
+            monitor: &self.monitor,
+            mode: self.mode,
+            _guard: PhantomData,
+        })
+    }
+
+    pub fn monitor(&self) -> &Arc<Monitor<T, C, R>> {
+        &self.monitor
+    }
+
+    pub fn signal_mode(&self) -> SignalMode {
+        self.mode
+    }
+
+    #[track_caller]
+    pub fn notify_one(&mut self) -> usize {
+        self.guard().notify_one()
+    }
+
+    #[track_caller]
+    pub fn notify_n(&mut self, n: usize) -> usize {
+        self.guard().notify_n(n)
+    }
+
+    #[track_caller]
+    pub fn notify_all(&mut self) -> usize {
+        self.guard().notify_all()
+    }
+
+    #[track_caller]
+    pub fn wait(&mut self) -> WaitOutcome {
+        self.guard().wait()
+    }
+
+    #[track_caller]
+    pub fn wait_for(&mut self, timeout: Duration) -> WaitOutcome {
+        self.guard().wait_for(timeout)
+    }
+
+    #[track_caller]
+    pub fn wait_until(&mut self, timeout: Instant) -> WaitOutcome {
+        self.guard().wait_until(timeout)
+    }
+
+    #[track_caller]
+    pub fn wait_while<F>(&mut self, condition: F) -> WaitOutcome
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.guard().wait_while(condition)
+    }
+
+    #[track_caller]
+    pub fn wait_while_for<F>(&mut self, timeout: Duration, condition: F) -> WaitOutcome
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.guard().wait_while_for(timeout, condition)
+    }
+
+    #[track_caller]
+    pub fn wait_while_until<F>(&mut self, timeout: Instant, condition: F) -> WaitOutcome
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.guard().wait_while_until(timeout, condition)
+    }
+
+    #[track_caller]
+    pub fn notify_one_on(&mut self, cond: C) -> usize {
+        self.guard().notify_one_on(cond)
+    }
+
+    #[track_caller]
+    pub fn notify_n_on(&mut self, cond: C, n: usize) -> usize {
+        self.guard().notify_n_on(cond, n)
+    }
+
+    #[track_caller]
+    pub fn notify_all_on(&mut self, cond: C) -> usize {
+        self.guard().notify_all_on(cond)
+    }
+
+    pub fn close(&mut self) -> usize {
+        self.guard().close()
+    }
+
+    #[track_caller]
+    pub fn wait_on(&mut self, cond: C) -> WaitOutcome {
+        self.guard().wait_on(cond)
+    }
+
+    #[track_caller]
+    pub fn wait_on_for(&mut self, cond: C, timeout: Duration) -> WaitOutcome {
+        self.guard().wait_on_for(cond, timeout)
+    }
+
+    #[track_caller]
+    pub fn wait_on_until(&mut self, cond: C, timeout: Instant) -> WaitOutcome {
+        self.guard().wait_on_until(cond, timeout)
+    }
+
+    #[track_caller]
+    pub fn wait_while_on<F>(&mut self, cond: C, condition: F) -> WaitOutcome
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.guard().wait_while_on(cond, condition)
+    }
+
+    #[track_caller]
+    pub fn wait_while_on_for<F>(&mut self, cond: C, timeout: Duration, condition: F) -> WaitOutcome
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.guard().wait_while_on_for(cond, timeout, condition)
+    }
+
+    #[track_caller]
+    pub fn wait_while_on_until<F>(&mut self, cond: C, timeout: Instant, condition: F) -> WaitOutcome
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.guard().wait_while_on_until(cond, timeout, condition)
+    }
+
+    #[track_caller]
+    pub fn wait_with_priority(&mut self, priority: i32) -> WaitOutcome {
+        self.guard().wait_with_priority(priority)
+    }
+
+    #[track_caller]
+    pub fn wait_with_priority_for(&mut self, priority: i32, timeout: Duration) -> WaitOutcome {
+        self.guard().wait_with_priority_for(priority, timeout)
+    }
+
+    #[track_caller]
+    pub fn wait_with_priority_until(&mut self, priority: i32, timeout: Instant) -> WaitOutcome {
+        self.guard().wait_with_priority_until(priority, timeout)
+    }
+
+    #[track_caller]
+    pub fn wait_while_with_priority<F>(&mut self, priority: i32, condition: F) -> WaitOutcome
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.guard().wait_while_with_priority(priority, condition)
+    }
+
+    #[track_caller]
+    pub fn wait_on_with_priority(&mut self, cond: C, priority: i32) -> WaitOutcome {
+        self.guard().wait_on_with_priority(cond, priority)
+    }
+
+    #[track_caller]
+    pub fn wait_while_on_with_priority<F>(
+        &mut self,
+        cond: C,
+        priority: i32,
+        condition: F,
+    ) -> WaitOutcome
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.guard()
+            .wait_while_on_with_priority(cond, priority, condition)
+    }
+
+    #[track_caller]
+    pub fn wait_cancellable(&mut self, token: &CancellationToken) -> WaitOutcome {
+        self.guard().wait_cancellable(token)
+    }
+
+    #[track_caller]
+    pub fn wait_cancellable_for(
+        &mut self,
+        token: &CancellationToken,
+        timeout: Duration,
+    ) -> WaitOutcome {
+        self.guard().wait_cancellable_for(token, timeout)
+    }
+
+    #[track_caller]
+    pub fn wait_cancellable_until(
+        &mut self,
+        token: &CancellationToken,
+        timeout: Instant,
+    ) -> WaitOutcome {
+        self.guard().wait_cancellable_until(token, timeout)
+    }
+
+    #[track_caller]
+    pub fn wait_while_cancellable<F>(
+        &mut self,
+        token: &CancellationToken,
+        condition: F,
+    ) -> WaitOutcome
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.guard().wait_while_cancellable(token, condition)
+    }
+
+    #[track_caller]
+    pub fn wait_while_cancellable_for<F>(
+        &mut self,
+        token: &CancellationToken,
+        timeout: Duration,
+        condition: F,
+    ) -> WaitOutcome
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.guard()
+            .wait_while_cancellable_for(token, timeout, condition)
+    }
+
+    #[track_caller]
+    pub fn wait_while_cancellable_until<F>(
+        &mut self,
+        token: &CancellationToken,
+        timeout: Instant,
+        condition: F,
+    ) -> WaitOutcome
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.guard()
+            .wait_while_cancellable_until(token, timeout, condition)
+    }
+
+    #[track_caller]
+    pub fn wait_on_cancellable(&mut self, cond: C, token: &CancellationToken) -> WaitOutcome {
+        self.guard().wait_on_cancellable(cond, token)
+    }
+
+    #[track_caller]
+    pub fn wait_while_on_cancellable<F>(
+        &mut self,
+        cond: C,
+        token: &CancellationToken,
+        condition: F,
+    ) -> WaitOutcome
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.guard()
+            .wait_while_on_cancellable(cond, token, condition)
+    }
+
+    pub fn bump(&mut self) {
+        self.guard().bump()
+    }
+
+    #[track_caller]
+    pub fn unlocked<F, U>(&mut self, f: F) -> U
+    where
+        F: FnOnce() -> U,
+    {
+        self.guard().unlocked(f)
+    }
+}
+
+impl<T, C: ConditionKey, R: RawMutexFair> ArcMonitorGuard<T, C, R> {
+    pub fn unlock_fair(self) {
+        let this = ManuallyDrop::new(self);
+        let monitor = unsafe { ptr::read(&this.monitor) };
+        MonitorGuard::new(&monitor).unlock_fair();
+    }
+
+    pub fn bump_fair(&mut self) {
+        self.guard().bump_fair()
+    }
+}
+
+impl<T, C, R: RawMutexHandoff> ArcMonitorGuard<T, C, R> {
+    pub fn set_signal_mode(&mut self, mode: SignalMode) {
+        self.mode = mode;
+    }
+}
+
+impl<T, C, R: lock_api::RawMutex> Drop for ArcMonitorGuard<T, C, R> {
+    fn drop(&mut self) {
+        order::released(self.monitor.key(), self.monitor.level);
+        unsafe { self.monitor.release() };
+    }
+}
+
+impl<T, C, R: lock_api::RawMutex> Deref for ArcMonitorGuard<T, C, R> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.monitor.mutex.data_ptr() }
+    }
+}
+
+impl<T, C, R: lock_api::RawMutex> DerefMut for ArcMonitorGuard<T, C, R> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.monitor.mutex.data_ptr() }
+    }
+}
@@ -0,0 +1,292 @@
+use crate::{
+    detect,
+    waiter::{WaitQueue, Waiter, Wakeup},
+    Mutex, RawMutex,
+};
+use alloc::sync::Arc;
+use core::{
+    fmt,
+    future::poll_fn,
+    marker::PhantomData,
+    mem,
+    ops::{Deref, DerefMut},
+};
+use lock_api::RawMutex as _;
+
+pub struct AsyncMonitor<T> {
+    mutex: Mutex<T>,
+    queues: Mutex<Queues>,
+}
+
+// Unlike `Monitor`, the queues get their own lock: a task that stops polling a
+// lock or wait future has to unregister itself without owning the monitor.
+#[derive(Default)]
+struct Queues {
+    entry: WaitQueue,
+    condition: WaitQueue,
+}
+
+impl<T> AsyncMonitor<T> {
+    pub fn new(t: T) -> Self {
+        AsyncMonitor {
+            mutex: Mutex::new(t),
+            queues: Mutex::new(Queues::default()),
+        }
+    }
+
+    pub async fn lock(&self) -> AsyncMonitorGuard<'_, T> {
+        if let Some(waiter) = self.enter(Waiter::task) {
+            Registration::new(self, waiter, Queue::Entry).wait().await;
+            detect::received(self.key());
+        }
+        AsyncMonitorGuard::new(self)
+    }
+
+    pub fn lock_blocking(&self) -> AsyncMonitorGuard<'_, T> {
+        if let Some(waiter) = self.enter(Waiter::new) {
+            waiter.park(None);
+            detect::received(self.key());
+        }
+        AsyncMonitorGuard::new(self)
+    }
+
+    pub fn try_lock(&self) -> Option<AsyncMonitorGuard<'_, T>> {
+        self.raw_mutex()
+            .try_lock()
+            .then(|| AsyncMonitorGuard::new(self))
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.mutex.is_locked()
+    }
+
+    pub fn into_inner(self) -> T {
+        self.mutex.into_inner()
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.mutex.get_mut()
+    }
+
+    fn raw_mutex(&self) -> &RawMutex {
+        unsafe { self.mutex.raw() }
+    }
+
+    fn key(&self) -> usize {
+        self.raw_mutex() as *const RawMutex as usize
+    }
+
+    // Takes the lock if it is free, otherwise queues a waiter that the lock
+    // will be handed to on release.
+    fn enter(&self, waiter: fn() -> Arc<Waiter>) -> Option<Arc<Waiter>> {
+        let mut queues = self.queues.lock();
+        if self.raw_mutex().try_lock() {
+            return None;
+        }
+        let waiter = waiter();
+        queues.entry.push(Arc::clone(&waiter), 0);
+        Some(waiter)
+    }
+
+    // Safety: the caller must own the monitor's lock, which is either handed
+    // to the longest-waiting locker or unlocked. A handed-off lock is unlocked
+    // by whichever thread polls the locker next, so the crate's own
+    // `RawMutexHandoff` lock is used rather than a generic one.
+    unsafe fn release(&self) {
+        let mut queues = self.queues.lock();
+        if queues.entry.handoff() {
+            detect::handoff(self.key());
+        } else {
+            self.raw_mutex().unlock();
+        }
+    }
+}
+
+impl<T> From<T> for AsyncMonitor<T> {
+    fn from(t: T) -> Self {
+        AsyncMonitor::new(t)
+    }
+}
+
+impl<T: Default> Default for AsyncMonitor<T> {
+    fn default() -> Self {
+        AsyncMonitor::new(T::default())
+    }
+}
+
+// Goes through a guard rather than the mutex's own `Debug`, which would unlock
+// it directly and strand anyone who queued for the lock in the meantime.
+impl<T: fmt::Debug> fmt::Debug for AsyncMonitor<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        struct LockedPlaceholder;
+        impl fmt::Debug for LockedPlaceholder {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("<locked>")
+            }
+        }
+
+        let mut d = f.debug_struct("AsyncMonitor");
+        match self.try_lock() {
+            Some(guard) => d.field("data", &&*guard).field("locked", &false),
+            None => d.field("data", &LockedPlaceholder).field("locked", &true),
+        };
+        d.finish()
+    }
+}
+
+pub struct AsyncMonitorGuard<'a, T> {
+    monitor: &'a AsyncMonitor<T>,
+    _guard: PhantomData<&'a mut T>,
+}
+
+impl<'a, T> AsyncMonitorGuard<'a, T> {
+    fn new(monitor: &'a AsyncMonitor<T>) -> Self {
+        AsyncMonitorGuard {
+            monitor,
+            _guard: PhantomData,
+        }
+    }
+
+    pub fn notify_one(&self) -> usize {
+        self.notify_n(1)
+    }
+
+    pub fn notify_n(&self, n: usize) -> usize {
+        self.monitor.queues.lock().condition.notify(n)
+    }
+
+    pub fn notify_all(&self) -> usize {
+        self.notify_n(usize::MAX)
+    }
+
+    pub async fn wait(self) -> AsyncMonitorGuard<'a, T> {
+        let monitor = self.monitor;
+        let waiter = self.enqueue(Waiter::task);
+        mem::forget(self);
+        unsafe { monitor.release() };
+
+        Registration::new(monitor, waiter, Queue::Condition)
+            .wait()
+            .await;
+        monitor.lock().await
+    }
+
+    pub async fn wait_while<F>(mut self, mut condition: F) -> AsyncMonitorGuard<'a, T>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        while condition(&mut self) {
+            self = self.wait().await;
+        }
+        self
+    }
+
+    pub fn wait_blocking(&mut self) {
+        let monitor = self.monitor;
+        let waiter = self.enqueue(Waiter::new);
+        unsafe { monitor.release() };
+
+        waiter.park(None);
+        mem::forget(monitor.lock_blocking());
+    }
+
+    pub fn wait_while_blocking<F>(&mut self, mut condition: F)
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        while condition(self) {
+            self.wait_blocking();
+        }
+    }
+
+    fn enqueue(&self, waiter: fn() -> Arc<Waiter>) -> Arc<Waiter> {
+        let waiter = waiter();
+        self.monitor
+            .queues
+            .lock()
+            .condition
+            .push(Arc::clone(&waiter), 0);
+        waiter
+    }
+}
+
+impl<T> Drop for AsyncMonitorGuard<'_, T> {
+    fn drop(&mut self) {
+        unsafe { self.monitor.release() };
+    }
+}
+
+impl<T> Deref for AsyncMonitorGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.monitor.mutex.data_ptr() }
+    }
+}
+
+impl<T> DerefMut for AsyncMonitorGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.monitor.mutex.data_ptr() }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Queue {
+    Entry,
+    Condition,
+}
+
+// A task parked in one of the queues. If its future is dropped before the
+// wakeup arrives, whatever the wakeup carried is passed on: a handed-off lock
+// is released and a notification goes to the next waiter.
+struct Registration<'a, T> {
+    monitor: &'a AsyncMonitor<T>,
+    waiter: Arc<Waiter>,
+    queue: Queue,
+    done: bool,
+}
+
+impl<'a, T> Registration<'a, T> {
+    fn new(monitor: &'a AsyncMonitor<T>, waiter: Arc<Waiter>, queue: Queue) -> Self {
+        Registration {
+            monitor,
+            waiter,
+            queue,
+            done: false,
+        }
+    }
+
+    async fn wait(mut self) -> Wakeup {
+        let wakeup = poll_fn(|cx| self.waiter.poll(cx)).await;
+        self.done = true;
+        wakeup
+    }
+}
+
+impl<T> Drop for Registration<'_, T> {
+    fn drop(&mut self) {
+        if self.done {
+            return;
+        }
+
+        let mut queues = self.monitor.queues.lock();
+        let queue = match self.queue {
+            Queue::Entry => &mut queues.entry,
+            Queue::Condition => &mut queues.condition,
+        };
+        if self.waiter.cancel() {
+            queue.remove(&self.waiter);
+            return;
+        }
+
+        match self.queue {
+            Queue::Entry => {
+                drop(queues);
+                unsafe { self.monitor.release() };
+            }
+            Queue::Condition => {
+                queue.notify(1);
+            }
+        }
+    }
+}
@@ -0,0 +1,251 @@
+use crate::{
+    detect,
+    waiter::{Waiter, Wakeup},
+    WaitOutcome,
+};
+use parking_lot::{
+    lock_api::{RawMutex as _, RawMutexTimed as _},
+    Mutex, MutexGuard, RawMutex,
+};
+use std::{
+    cell::UnsafeCell,
+    fmt,
+    marker::PhantomData,
+    mem,
+    ops::{Deref, DerefMut},
+    panic::{self, AssertUnwindSafe},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+type Predicate<T> = dyn FnMut(&T) -> bool + Send;
+
+pub struct AutoMonitor<T> {
+    mutex: Mutex<T>,
+    waiters: UnsafeCell<Vec<AutoWaiter<T>>>,
+}
+
+unsafe impl<T: Send> Send for AutoMonitor<T> {}
+unsafe impl<T: Send> Sync for AutoMonitor<T> {}
+
+// The predicate lives on the stack of the thread blocked in `await_condition*`,
+// which always unregisters it under the lock before returning.
+struct AutoWaiter<T> {
+    waiter: Arc<Waiter>,
+    predicate: *mut Predicate<T>,
+}
+
+impl<T> AutoMonitor<T> {
+    pub fn new(t: T) -> Self {
+        AutoMonitor {
+            mutex: Mutex::new(t),
+            waiters: UnsafeCell::new(Vec::new()),
+        }
+    }
+
+    pub fn lock(&self) -> AutoMonitorGuard<'_, T> {
+        self.raw_mutex().lock();
+        AutoMonitorGuard::new(self)
+    }
+
+    pub fn try_lock(&self) -> Option<AutoMonitorGuard<'_, T>> {
+        self.raw_mutex()
+            .try_lock()
+            .then(|| AutoMonitorGuard::new(self))
+    }
+
+    pub fn try_lock_for(&self, timeout: Duration) -> Option<AutoMonitorGuard<'_, T>> {
+        self.raw_mutex()
+            .try_lock_for(timeout)
+            .then(|| AutoMonitorGuard::new(self))
+    }
+
+    pub fn try_lock_until(&self, timeout: Instant) -> Option<AutoMonitorGuard<'_, T>> {
+        self.raw_mutex()
+            .try_lock_until(timeout)
+            .then(|| AutoMonitorGuard::new(self))
+    }
+
+    pub fn with_lock<U, F>(&self, f: F) -> U
+    where
+        F: FnOnce(AutoMonitorGuard<'_, T>) -> U,
+    {
+        f(self.lock())
+    }
+
+    pub fn into_inner(self) -> T {
+        self.mutex.into_inner()
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.mutex.get_mut()
+    }
+
+    fn raw_mutex(&self) -> &RawMutex {
+        unsafe { self.mutex.raw() }
+    }
+
+    fn key(&self) -> usize {
+        self.raw_mutex() as *const RawMutex as usize
+    }
+
+    // Safety: the caller must own the monitor's lock.
+    unsafe fn with_waiters<U>(&self, f: impl FnOnce(&mut Vec<AutoWaiter<T>>) -> U) -> U {
+        f(&mut *self.waiters.get())
+    }
+
+    // Safety: the caller must own the monitor's lock. It is handed straight to
+    // the longest-waiting thread whose predicate holds, if there is one, which
+    // then unlocks it; parking_lot's mutex is `RawMutexHandoff` and allows that.
+    //
+    // Predicates run on whichever thread releases the lock, often from a
+    // guard's `Drop`. One that panics is caught and counted as holding, so its
+    // own thread is handed the lock and runs it again, and the panic surfaces
+    // there rather than leaving the lock held or aborting a thread that is
+    // already unwinding.
+    unsafe fn release(&self) {
+        let data = &*self.mutex.data_ptr();
+        let selected = self.with_waiters(|waiters| {
+            let mut selected = None;
+            waiters.retain(|w| {
+                if selected.is_some() {
+                    return true;
+                }
+                let holds = panic::catch_unwind(AssertUnwindSafe(|| (*w.predicate)(data)));
+                if let Ok(false) = holds {
+                    return true;
+                }
+                if w.waiter.select() {
+                    selected = Some(Arc::clone(&w.waiter));
+                }
+                false
+            });
+            selected
+        });
+
+        match selected {
+            Some(waiter) => {
+                detect::handoff(self.key());
+                waiter.complete_handoff();
+            }
+            None => self.raw_mutex().unlock(),
+        }
+    }
+}
+
+impl<T> From<T> for AutoMonitor<T> {
+    fn from(t: T) -> Self {
+        AutoMonitor::new(t)
+    }
+}
+
+impl<T: Default> Default for AutoMonitor<T> {
+    fn default() -> Self {
+        AutoMonitor::new(T::default())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for AutoMonitor<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AutoMonitor")
+            .field("mutex", &self.mutex)
+            .finish()
+    }
+}
+
+pub struct AutoMonitorGuard<'a, T> {
+    monitor: &'a AutoMonitor<T>,
+    _guard: PhantomData<MutexGuard<'a, T>>,
+}
+
+impl<'a, T> AutoMonitorGuard<'a, T> {
+    fn new(monitor: &'a AutoMonitor<T>) -> Self {
+        AutoMonitorGuard {
+            monitor,
+            _guard: PhantomData,
+        }
+    }
+
+    pub fn await_condition<F>(&mut self, condition: F)
+    where
+        F: FnMut(&T) -> bool + Send,
+    {
+        self.park_until(None, condition);
+    }
+
+    pub fn await_condition_for<F>(&mut self, timeout: Duration, condition: F) -> WaitOutcome
+    where
+        F: FnMut(&T) -> bool + Send,
+    {
+        self.park_until(Instant::now().checked_add(timeout), condition)
+    }
+
+    pub fn await_condition_until<F>(&mut self, timeout: Instant, condition: F) -> WaitOutcome
+    where
+        F: FnMut(&T) -> bool + Send,
+    {
+        self.park_until(Some(timeout), condition)
+    }
+
+    // A lock handed over by `release` comes with the predicate having just
+    // held or panicked, so it is run once more here either way: a panic then
+    // unwinds from this call, with the lock released by the guard's drop.
+    fn park_until<F>(&mut self, deadline: Option<Instant>, mut condition: F) -> WaitOutcome
+    where
+        F: FnMut(&T) -> bool + Send,
+    {
+        let monitor = self.monitor;
+        while !condition(self) {
+            let predicate: &mut (dyn FnMut(&T) -> bool + Send + '_) = &mut condition;
+            let predicate: *mut Predicate<T> = unsafe { mem::transmute(predicate) };
+            let waiter = Waiter::new();
+            unsafe {
+                monitor.with_waiters(|waiters| {
+                    waiters.push(AutoWaiter {
+                        waiter: Arc::clone(&waiter),
+                        predicate,
+                    })
+                });
+                monitor.release();
+            }
+
+            match waiter.park(deadline) {
+                Wakeup::TimedOut => {
+                    monitor.raw_mutex().lock();
+                    unsafe {
+                        monitor.with_waiters(|waiters| {
+                            waiters.retain(|w| !Arc::ptr_eq(&w.waiter, &waiter))
+                        });
+                    }
+                    return if condition(self) {
+                        WaitOutcome::satisfied(deadline)
+                    } else {
+                        WaitOutcome::TimedOut
+                    };
+                }
+                _ => detect::received(monitor.key()),
+            }
+        }
+        WaitOutcome::satisfied(deadline)
+    }
+}
+
+impl<T> Drop for AutoMonitorGuard<'_, T> {
+    fn drop(&mut self) {
+        unsafe { self.monitor.release() };
+    }
+}
+
+impl<T> Deref for AutoMonitorGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.monitor.mutex.data_ptr() }
+    }
+}
+
+impl<T> DerefMut for AutoMonitorGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.monitor.mutex.data_ptr() }
+    }
+}
@@ -0,0 +1,164 @@
+// Threads blocked in a monitor park on their waiter's address through the
+// installed `Backend`. With the `std` feature the default uses parking_lot's
+// parking lot and `std::time`; without it the default spins and has no clock,
+// so only untimed waits work until a backend with one is installed.
+
+use crate::Instant;
+use core::{
+    cell::UnsafeCell,
+    fmt, hint,
+    sync::atomic::{AtomicBool, AtomicU8, Ordering},
+    time::Duration,
+};
+use lock_api::GuardSend;
+
+pub trait Backend: Sync {
+    // Blocks the calling thread on `key` until `unpark` is called with the
+    // same key or `deadline` passes. `validate` is checked before blocking, and
+    // must be serialized with `unpark` so that a wakeup between the two is not
+    // lost. Returning early is always allowed; callers re-check and park again.
+    fn park(&self, key: usize, validate: &dyn Fn() -> bool, deadline: Option<Instant>);
+
+    fn unpark(&self, key: usize);
+
+    // Monotonic time since some fixed point, backing `Instant` without the
+    // `std` feature. With it, `Instant` is `std::time::Instant` instead.
+    fn now(&self) -> Duration;
+}
+
+const UNSET: u8 = 0;
+const SETTING: u8 = 1;
+const SET: u8 = 2;
+
+static STATE: AtomicU8 = AtomicU8::new(UNSET);
+static BACKEND: Slot = Slot(UnsafeCell::new(&DEFAULT));
+
+// Only written while `STATE` is `SETTING`, which nothing else can observe as
+// finished until it has moved on to `SET`.
+struct Slot(UnsafeCell<&'static dyn Backend>);
+
+unsafe impl Sync for Slot {}
+
+// Waiters parked on one backend could never be woken through another, so the
+// backend can only be installed before the first one is looked up.
+pub fn set_backend(backend: &'static dyn Backend) -> Result<(), SetBackendError> {
+    STATE
+        .compare_exchange(UNSET, SETTING, Ordering::Acquire, Ordering::Relaxed)
+        .map_err(|_| SetBackendError)?;
+    unsafe { *BACKEND.0.get() = backend };
+    STATE.store(SET, Ordering::Release);
+    Ok(())
+}
+
+pub(crate) fn get() -> &'static dyn Backend {
+    loop {
+        match STATE.load(Ordering::Acquire) {
+            UNSET => {
+                let _ = STATE.compare_exchange(UNSET, SET, Ordering::Acquire, Ordering::Relaxed);
+            }
+            SETTING => hint::spin_loop(),
+            _ => return unsafe { *BACKEND.0.get() },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetBackendError;
+
+impl fmt::Display for SetBackendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a parking backend is already in use")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SetBackendError {}
+
+#[cfg(feature = "std")]
+static DEFAULT: ParkingLot = ParkingLot;
+
+#[cfg(feature = "std")]
+struct ParkingLot;
+
+#[cfg(feature = "std")]
+impl Backend for ParkingLot {
+    fn park(&self, key: usize, validate: &dyn Fn() -> bool, deadline: Option<Instant>) {
+        // Waiter addresses are never parked on by anything else.
+        unsafe {
+            parking_lot_core::park(
+                key,
+                validate,
+                || {},
+                |_, _| {},
+                parking_lot_core::DEFAULT_PARK_TOKEN,
+                deadline,
+            )
+        };
+    }
+
+    fn unpark(&self, key: usize) {
+        unsafe { parking_lot_core::unpark_all(key, parking_lot_core::DEFAULT_UNPARK_TOKEN) };
+    }
+
+    fn now(&self) -> Duration {
+        static EPOCH: std::sync::OnceLock<Instant> = std::sync::OnceLock::new();
+        EPOCH.get_or_init(Instant::now).elapsed()
+    }
+}
+
+#[cfg(not(feature = "std"))]
+static DEFAULT: Spin = Spin;
+
+#[cfg(not(feature = "std"))]
+struct Spin;
+
+#[cfg(not(feature = "std"))]
+impl Backend for Spin {
+    fn park(&self, _key: usize, validate: &dyn Fn() -> bool, _deadline: Option<Instant>) {
+        if validate() {
+            hint::spin_loop();
+        }
+    }
+
+    fn unpark(&self, _key: usize) {}
+
+    fn now(&self) -> Duration {
+        panic!("no clock without the `std` feature; install a backend with `set_backend`")
+    }
+}
+
+// The default lock without the `std` feature, usable with any backend.
+pub struct RawSpinMutex {
+    locked: AtomicBool,
+}
+
+unsafe impl lock_api::RawMutex for RawSpinMutex {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const INIT: Self = RawSpinMutex {
+        locked: AtomicBool::new(false),
+    };
+
+    type GuardMarker = GuardSend;
+
+    fn lock(&self) {
+        while !self.try_lock() {
+            while self.locked.load(Ordering::Relaxed) {
+                hint::spin_loop();
+            }
+        }
+    }
+
+    fn try_lock(&self) -> bool {
+        self.locked
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+    }
+
+    unsafe fn unlock(&self) {
+        self.locked.store(false, Ordering::Release);
+    }
+
+    fn is_locked(&self) -> bool {
+        self.locked.load(Ordering::Relaxed)
+    }
+}
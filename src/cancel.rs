@@ -0,0 +1,62 @@
+use crate::{waiter::Waiter, Mutex};
+use alloc::{sync::Arc, vec::Vec};
+use core::{
+    fmt, mem,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    cancelled: AtomicBool,
+    waiters: Mutex<Vec<Arc<Waiter>>>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        CancellationToken::default()
+    }
+
+    pub fn cancel(&self) {
+        let waiters = {
+            let mut waiters = self.inner.waiters.lock();
+            self.inner.cancelled.store(true, Ordering::Release);
+            mem::take(&mut *waiters)
+        };
+        for waiter in waiters {
+            waiter.cancel();
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn register(&self, waiter: &Arc<Waiter>) -> bool {
+        let mut waiters = self.inner.waiters.lock();
+        if self.is_cancelled() {
+            return false;
+        }
+        waiters.push(Arc::clone(waiter));
+        true
+    }
+
+    pub(crate) fn unregister(&self, waiter: &Arc<Waiter>) {
+        self.inner
+            .waiters
+            .lock()
+            .retain(|w| !Arc::ptr_eq(w, waiter));
+    }
+}
+
+impl fmt::Debug for CancellationToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancellationToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
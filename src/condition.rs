@@ -0,0 +1,13 @@
+pub trait ConditionKey {
+    const COUNT: usize;
+
+    fn index(self) -> usize;
+}
+
+impl ConditionKey for usize {
+    const COUNT: usize = 0;
+
+    fn index(self) -> usize {
+        self
+    }
+}
@@ -0,0 +1,62 @@
+use crate::detect::{Registry, REGISTRY};
+use std::thread::ThreadId;
+
+// Threads stuck in `wait` are not reported: any thread can notify, including
+// ones that have never touched a monitor or only cancel a token, so there is
+// no telling that a waiter has nobody left to wake it.
+#[derive(Debug)]
+pub enum Deadlock {
+    // Threads blocked in `Monitor::lock`, each waiting on a lock held by the
+    // next, as found by parking_lot's detector.
+    LockCycle(Vec<BlockedThread>),
+}
+
+#[derive(Debug, Clone)]
+pub struct BlockedThread {
+    thread_id: ThreadId,
+    name: Option<String>,
+    backtrace: String,
+}
+
+impl BlockedThread {
+    pub fn thread_id(&self) -> ThreadId {
+        self.thread_id
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn backtrace(&self) -> &str {
+        &self.backtrace
+    }
+}
+
+// Each cycle is reported once, like `parking_lot::deadlock::check_deadlock`.
+pub fn check() -> Vec<Deadlock> {
+    let cycles = parking_lot::deadlock::check_deadlock();
+    let registry = REGISTRY.lock();
+
+    cycles
+        .iter()
+        .map(|cycle| {
+            let threads = cycle
+                .iter()
+                .map(|t| BlockedThread {
+                    thread_id: t.thread_id(),
+                    name: name(&registry, t.thread_id()),
+                    backtrace: format!("{:?}", t.backtrace()),
+                })
+                .collect();
+            Deadlock::LockCycle(threads)
+        })
+        .collect()
+}
+
+fn name(registry: &Registry, id: ThreadId) -> Option<String> {
+    registry
+        .threads
+        .iter()
+        .find(|e| e.id == id)
+        .and_then(|e| e.name.clone())
+}
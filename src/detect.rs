@@ -0,0 +1,86 @@
+// Bookkeeping for the `deadlock_detection` feature. Without the feature every
+// hook is a no-op.
+
+#[cfg(feature = "deadlock_detection")]
+pub(crate) use enabled::*;
+
+#[cfg(not(feature = "deadlock_detection"))]
+pub(crate) use disabled::*;
+
+#[cfg(feature = "deadlock_detection")]
+mod enabled {
+    use parking_lot::{const_mutex, Mutex};
+    use parking_lot_core::deadlock::{acquire_resource, release_resource};
+    use std::thread::{self, ThreadId};
+
+    pub(crate) static REGISTRY: Mutex<Registry> = const_mutex(Registry::new());
+
+    // Every live thread that has locked a `Monitor`, so that threads in a
+    // reported lock cycle can be named.
+    pub(crate) struct Registry {
+        pub(crate) threads: Vec<Entry>,
+    }
+
+    pub(crate) struct Entry {
+        pub(crate) id: ThreadId,
+        pub(crate) name: Option<String>,
+    }
+
+    impl Registry {
+        const fn new() -> Self {
+            Registry {
+                threads: Vec::new(),
+            }
+        }
+    }
+
+    struct Registration(ThreadId);
+
+    impl Registration {
+        fn new() -> Self {
+            let thread = thread::current();
+            REGISTRY.lock().threads.push(Entry {
+                id: thread.id(),
+                name: thread.name().map(String::from),
+            });
+            Registration(thread.id())
+        }
+    }
+
+    impl Drop for Registration {
+        fn drop(&mut self) {
+            REGISTRY.lock().threads.retain(|e| e.id != self.0);
+        }
+    }
+
+    thread_local! {
+        static REGISTRATION: Registration = Registration::new();
+    }
+
+    pub(crate) fn acquired(_key: usize) {
+        let _ = REGISTRATION.try_with(|_| ());
+    }
+
+    // A lock handed to another thread never passes through parking_lot's
+    // unlock and lock paths, so its resource tracking has to be moved along
+    // by hand.
+    pub(crate) fn handoff(key: usize) {
+        unsafe { release_resource(key) };
+    }
+
+    pub(crate) fn received(key: usize) {
+        unsafe { acquire_resource(key) };
+    }
+}
+
+#[cfg(not(feature = "deadlock_detection"))]
+mod disabled {
+    #[inline]
+    pub(crate) fn acquired(_key: usize) {}
+
+    #[inline]
+    pub(crate) fn handoff(_key: usize) {}
+
+    #[inline]
+    pub(crate) fn received(_key: usize) {}
+}
@@ -1,141 +1,1138 @@
-use parking_lot::{Condvar, Mutex, MutexGuard, RawMutex, WaitTimeoutResult};
-use std::{
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+mod arc;
+mod async_monitor;
+#[cfg(feature = "std")]
+mod auto;
+mod backend;
+mod cancel;
+mod condition;
+#[cfg(feature = "deadlock_detection")]
+pub mod deadlock;
+mod detect;
+#[cfg(not(feature = "std"))]
+mod instant;
+mod mapped;
+mod order;
+mod outcome;
+#[cfg(feature = "std")]
+mod reentrant;
+#[cfg(feature = "std")]
+mod rw_monitor;
+mod select;
+mod stats;
+mod trace;
+mod waiter;
+
+pub use arc::ArcMonitorGuard;
+pub use async_monitor::{AsyncMonitor, AsyncMonitorGuard};
+#[cfg(feature = "std")]
+pub use auto::{AutoMonitor, AutoMonitorGuard};
+pub use backend::{set_backend, Backend, RawSpinMutex, SetBackendError};
+pub use cancel::CancellationToken;
+pub use condition::ConditionKey;
+#[cfg(not(feature = "std"))]
+pub use instant::Instant;
+pub use mapped::{MappedMonitorGuard, Remapped};
+pub use outcome::WaitOutcome;
+#[cfg(feature = "std")]
+pub use reentrant::{ReentrantMonitor, ReentrantMonitorGuard};
+#[cfg(feature = "std")]
+pub use rw_monitor::{
+    RwMonitor, RwMonitorReadGuard, RwMonitorUpgradableGuard, RwMonitorWriteGuard,
+};
+pub use select::{Select, SelectClosed, Selected};
+#[cfg(feature = "stats")]
+pub use stats::{Histogram, MonitorStats};
+#[cfg(feature = "std")]
+pub use std::time::Instant;
+
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+use core::{
+    cell::UnsafeCell,
+    fmt,
+    marker::PhantomData,
+    mem,
     ops::{Deref, DerefMut},
-    time::{Duration, Instant},
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    time::Duration,
 };
+use lock_api::{RawMutexFair, RawMutexTimed};
+#[cfg(feature = "std")]
+use parking_lot::RawMutex;
+use waiter::{WaitQueue, Waiter, Wakeup};
+
+#[cfg(not(feature = "std"))]
+use backend::RawSpinMutex as RawMutex;
+
+// For the crate's own bookkeeping, on whichever lock `Monitor` defaults to.
+type Mutex<T> = lock_api::Mutex<RawMutex, T>;
+
+pub struct Monitor<T, C = usize, R = RawMutex> {
+    mutex: lock_api::Mutex<R, T>,
+    state: UnsafeCell<State>,
+    waiting: Box<[AtomicUsize]>,
+    closed: AtomicBool,
+    stats: stats::Stats,
+    conditions: usize,
+    level: Option<u32>,
+    name: Option<&'static str>,
+    _condition: PhantomData<fn(C)>,
+}
+
+unsafe impl<T: Send, C, R: lock_api::RawMutex + Sync> Sync for Monitor<T, C, R> {}
 
-#[derive(Debug, Default)]
-pub struct Monitor<T> {
-    mutex: Mutex<T>,
-    cv: Condvar,
+// Queue 0 backs the unkeyed `wait`/`notify` methods, queue `i + 1` backs
+// condition `i`. Everything in here is guarded by the monitor's mutex.
+struct State {
+    queues: Box<[WaitQueue]>,
+    urgent: WaitQueue,
 }
 
 impl<T> Monitor<T> {
     pub fn new(t: T) -> Self {
+        Monitor::with_conditions(t, 0)
+    }
+
+    pub fn with_conditions(t: T, count: usize) -> Self {
+        Monitor::from_parts(<RawMutex as lock_api::RawMutex>::INIT, t, count)
+    }
+}
+
+impl<T, R: lock_api::RawMutex> Monitor<T, usize, R> {
+    pub fn from_raw(raw_mutex: R, t: T) -> Self {
+        Monitor::from_parts(raw_mutex, t, 0)
+    }
+}
+
+impl<T, C: ConditionKey, R: lock_api::RawMutex> Monitor<T, C, R> {
+    pub fn keyed(t: T) -> Self {
+        Monitor::from_parts(R::INIT, t, C::COUNT)
+    }
+
+    fn from_parts(raw_mutex: R, t: T, count: usize) -> Self {
         Monitor {
-            mutex: Mutex::new(t),
-            cv: Condvar::new(),
+            mutex: lock_api::Mutex::from_raw(raw_mutex, t),
+            state: UnsafeCell::new(State {
+                queues: (0..=count).map(|_| WaitQueue::default()).collect(),
+                urgent: WaitQueue::default(),
+            }),
+            waiting: (0..=count).map(|_| AtomicUsize::new(0)).collect(),
+            closed: AtomicBool::new(false),
+            stats: stats::Stats::new(),
+            conditions: count,
+            level: None,
+            name: None,
+            _condition: PhantomData,
         }
     }
 
-    pub fn lock(&self) -> MonitorGuard<T> {
-        MonitorGuard::new(&self.cv, self.mutex.lock())
+    pub fn leveled(self, level: u32) -> Self {
+        Monitor {
+            level: Some(level),
+            ..self
+        }
+    }
+
+    pub fn named(self, name: &'static str) -> Self {
+        Monitor {
+            name: Some(name),
+            ..self
+        }
+    }
+
+    pub fn conditions(&self) -> usize {
+        self.conditions
+    }
+
+    pub fn level(&self) -> Option<u32> {
+        self.level
     }
 
-    pub fn try_lock(&self) -> Option<MonitorGuard<T>> {
-        self.mutex
-            .try_lock()
-            .map(|g| MonitorGuard::new(&self.cv, g))
+    pub fn name(&self) -> Option<&'static str> {
+        self.name
     }
 
-    pub fn try_lock_for(&self, timeout: Duration) -> Option<MonitorGuard<T>> {
-        self.mutex
-            .try_lock_for(timeout)
-            .map(|g| MonitorGuard::new(&self.cv, g))
+    pub fn is_locked(&self) -> bool {
+        self.mutex.is_locked()
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Acquire)
+    }
+
+    pub fn waiter_count(&self) -> usize {
+        self.waiting.iter().map(|w| w.load(Ordering::Relaxed)).sum()
+    }
+
+    pub fn waiter_count_on(&self, cond: C) -> usize {
+        self.waiting[self.condition(cond)].load(Ordering::Relaxed)
+    }
+
+    fn condition(&self, cond: C) -> usize {
+        let index = cond.index();
+        if index >= self.conditions {
+            panic!(
+                "condition {} out of range for monitor with {} conditions",
+                index, self.conditions
+            );
+        }
+        index + 1
     }
 
-    pub fn try_lock_until(&self, timeout: Instant) -> Option<MonitorGuard<T>> {
-        self.mutex
-            .try_lock_until(timeout)
-            .map(|g| MonitorGuard::new(&self.cv, g))
+    #[track_caller]
+    pub fn lock(&self) -> MonitorGuard<'_, T, C, R> {
+        order::acquiring(self.level);
+        self.traced(|m| {
+            m.acquire();
+            true
+        });
+        self.guard()
     }
 
+    #[track_caller]
+    pub fn try_lock(&self) -> Option<MonitorGuard<'_, T, C, R>> {
+        order::acquiring(self.level);
+        if self.traced(Self::try_acquire) {
+            Some(self.guard())
+        } else {
+            None
+        }
+    }
+
+    #[track_caller]
+    pub fn lock_arc(self: &Arc<Self>) -> ArcMonitorGuard<T, C, R> {
+        order::acquiring(self.level);
+        self.traced(|m| {
+            m.acquire();
+            true
+        });
+        self.arc_guard()
+    }
+
+    #[track_caller]
+    pub fn try_lock_arc(self: &Arc<Self>) -> Option<ArcMonitorGuard<T, C, R>> {
+        order::acquiring(self.level);
+        if self.traced(Self::try_acquire) {
+            Some(self.arc_guard())
+        } else {
+            None
+        }
+    }
+
+    #[track_caller]
+    fn arc_guard(self: &Arc<Self>) -> ArcMonitorGuard<T, C, R> {
+        order::acquired(self.key(), self.level);
+        ArcMonitorGuard::new(Arc::clone(self))
+    }
+
+    // Wraps a lock the caller has just taken, recording it for lock-order
+    // checks.
+    #[track_caller]
+    fn guard(&self) -> MonitorGuard<'_, T, C, R> {
+        order::acquired(self.key(), self.level);
+        MonitorGuard::new(self)
+    }
+
+    #[track_caller]
     pub fn with_lock<U, F>(&self, f: F) -> U
     where
-        F: FnOnce(MonitorGuard<T>) -> U,
+        F: FnOnce(MonitorGuard<'_, T, C, R>) -> U,
     {
         f(self.lock())
     }
 
+    #[track_caller]
     pub fn try_with_lock<U, F>(&self, f: F) -> Option<U>
     where
-        F: FnOnce(MonitorGuard<T>) -> U,
+        F: FnOnce(MonitorGuard<'_, T, C, R>) -> U,
     {
         self.try_lock().map(f)
     }
 
+    pub fn into_inner(self) -> T {
+        self.mutex.into_inner()
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.mutex.get_mut()
+    }
+
+    /// # Safety
+    ///
+    /// See [`lock_api::Mutex::raw`].
+    pub unsafe fn raw(&self) -> &R {
+        self.mutex.raw()
+    }
+
+    /// # Safety
+    ///
+    /// See [`lock_api::Mutex::force_unlock`].
+    pub unsafe fn force_unlock(&self) {
+        self.mutex.force_unlock()
+    }
+}
+
+impl<T, C, R> Monitor<T, C, R>
+where
+    C: ConditionKey,
+    R: RawMutexTimed<Duration = Duration, Instant = Instant>,
+{
+    #[track_caller]
+    pub fn try_lock_for(&self, timeout: Duration) -> Option<MonitorGuard<'_, T, C, R>> {
+        order::acquiring(self.level);
+        if self.traced(|m| m.try_acquire_with(|raw| raw.try_lock_for(timeout))) {
+            Some(self.guard())
+        } else {
+            None
+        }
+    }
+
+    #[track_caller]
+    pub fn try_lock_until(&self, timeout: Instant) -> Option<MonitorGuard<'_, T, C, R>> {
+        order::acquiring(self.level);
+        if self.traced(|m| m.try_acquire_with(|raw| raw.try_lock_until(timeout))) {
+            Some(self.guard())
+        } else {
+            None
+        }
+    }
+
+    #[track_caller]
+    pub fn try_lock_arc_for(
+        self: &Arc<Self>,
+        timeout: Duration,
+    ) -> Option<ArcMonitorGuard<T, C, R>> {
+        order::acquiring(self.level);
+        if self.traced(|m| m.try_acquire_with(|raw| raw.try_lock_for(timeout))) {
+            Some(self.arc_guard())
+        } else {
+            None
+        }
+    }
+
+    #[track_caller]
+    pub fn try_lock_arc_until(
+        self: &Arc<Self>,
+        timeout: Instant,
+    ) -> Option<ArcMonitorGuard<T, C, R>> {
+        order::acquiring(self.level);
+        if self.traced(|m| m.try_acquire_with(|raw| raw.try_lock_until(timeout))) {
+            Some(self.arc_guard())
+        } else {
+            None
+        }
+    }
+
+    #[track_caller]
     pub fn try_with_lock_for<U, F>(&self, timeout: Duration, f: F) -> Option<U>
     where
-        F: FnOnce(MonitorGuard<T>) -> U,
+        F: FnOnce(MonitorGuard<'_, T, C, R>) -> U,
     {
         self.try_lock_for(timeout).map(f)
     }
 
+    #[track_caller]
     pub fn try_with_lock_until<U, F>(&self, timeout: Instant, f: F) -> Option<U>
     where
-        F: FnOnce(MonitorGuard<T>) -> U,
+        F: FnOnce(MonitorGuard<'_, T, C, R>) -> U,
     {
         self.try_lock_until(timeout).map(f)
     }
+}
 
-    pub fn into_inner(self) -> T {
-        self.mutex.into_inner()
+impl<T, C: ConditionKey, R: RawMutexFair> Monitor<T, C, R> {
+    /// # Safety
+    ///
+    /// See [`lock_api::Mutex::force_unlock_fair`].
+    pub unsafe fn force_unlock_fair(&self) {
+        self.mutex.force_unlock_fair()
     }
+}
 
-    pub fn get_mut(&mut self) -> &mut T {
-        self.mutex.get_mut()
+impl<T, C, R: lock_api::RawMutex> Monitor<T, C, R> {
+    fn raw_mutex(&self) -> &R {
+        unsafe { self.mutex.raw() }
     }
 
-    pub unsafe fn raw(&self) -> &RawMutex {
-        self.mutex.raw()
+    // Safety: the caller must own the monitor's lock.
+    unsafe fn with_state<U>(&self, f: impl FnOnce(&mut State) -> U) -> U {
+        f(&mut *self.state.get())
     }
 
-    pub unsafe fn force_unlock(&self) {
-        self.mutex.force_unlock()
+    // Identifies the monitor to the deadlock detector.
+    fn key(&self) -> usize {
+        self.raw_mutex() as *const R as usize
     }
 
-    pub unsafe fn force_unlock_fair(&self) {
-        self.mutex.force_unlock_fair()
+    // Runs one of the public lock methods' attempts at the lock inside its
+    // tracing span.
+    #[track_caller]
+    fn traced(&self, lock: impl FnOnce(&Self) -> bool) -> bool {
+        let trace = trace::locking(self.name, self.key());
+        let locked = lock(self);
+        trace.locked(locked);
+        locked
+    }
+
+    fn acquire(&self) {
+        let mut blocked = None;
+        if !self.raw_mutex().try_lock() {
+            blocked = Some(stats::Stamp::now());
+            self.raw_mutex().lock();
+        }
+        self.acquired(blocked);
+    }
+
+    fn try_acquire(&self) -> bool {
+        let locked = self.raw_mutex().try_lock();
+        if locked {
+            self.acquired(None);
+        }
+        locked
+    }
+
+    // Only a lock that could not be taken straight away counts as contended.
+    fn try_acquire_with(&self, lock: impl FnOnce(&R) -> bool) -> bool {
+        if self.try_acquire() {
+            return true;
+        }
+        let blocked = stats::Stamp::now();
+        let locked = lock(self.raw_mutex());
+        if locked {
+            self.acquired(Some(blocked));
+        }
+        locked
+    }
+
+    fn acquired(&self, blocked: Option<stats::Stamp>) {
+        detect::acquired(self.key());
+        self.stats.acquired(blocked);
+    }
+
+    // Safety: the caller must own the monitor's lock.
+    unsafe fn released(&self) {
+        self.stats.released();
+    }
+
+    // Safety: the caller must own the monitor's lock, and must not touch any
+    // state it guards afterwards.
+    unsafe fn hand_off(&self, waiter: &Waiter) {
+        self.released();
+        detect::handoff(self.key());
+        waiter.complete_handoff();
+    }
+
+    // Called by the thread a lock was handed to.
+    fn received(&self) {
+        detect::received(self.key());
+        self.acquired(None);
+    }
+
+    // Safety: the caller must own the monitor's lock, which is either passed on
+    // to the longest-waiting urgent signaller or unlocked.
+    unsafe fn release(&self) {
+        match self.with_state(|s| s.urgent.select()) {
+            Some(signaller) => self.hand_off(&signaller),
+            None => {
+                self.released();
+                self.raw_mutex().unlock();
+            }
+        }
+    }
+}
+
+impl<T, C, R: RawMutexFair> Monitor<T, C, R> {
+    // Safety: as for `release`. Urgent signallers already get the lock handed
+    // to them, so only a plain unlock needs to be made fair.
+    unsafe fn release_fair(&self) {
+        match self.with_state(|s| s.urgent.select()) {
+            Some(signaller) => self.hand_off(&signaller),
+            None => {
+                self.released();
+                self.raw_mutex().unlock_fair();
+            }
+        }
+    }
+
+    // Safety: the caller must own the monitor's lock, which it still owns on
+    // return.
+    unsafe fn bump_fair(&self) {
+        match self.with_state(|s| s.urgent.select()) {
+            Some(signaller) => {
+                self.hand_off(&signaller);
+                self.acquire();
+            }
+            None => {
+                self.released();
+                self.raw_mutex().bump();
+                self.acquired(None);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "stats")]
+impl<T, C, R: lock_api::RawMutex> Monitor<T, C, R> {
+    pub fn stats(&self) -> MonitorStats {
+        self.stats.snapshot()
+    }
+
+    pub fn reset_stats(&self) {
+        self.stats.reset()
     }
 }
 
-impl<T> From<T> for Monitor<T> {
+impl<T, C: ConditionKey, R: lock_api::RawMutex> From<T> for Monitor<T, C, R> {
     fn from(t: T) -> Self {
-        Monitor::new(t)
+        Monitor::keyed(t)
+    }
+}
+
+impl<T: Default, C: ConditionKey, R: lock_api::RawMutex> Default for Monitor<T, C, R> {
+    fn default() -> Self {
+        Monitor::keyed(T::default())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T, C, R> serde::Serialize for Monitor<T, C, R>
+where
+    T: serde::Serialize,
+    C: ConditionKey,
+    R: lock_api::RawMutex,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.lock().serialize(serializer)
+    }
+}
+
+/// Only the data round-trips. The monitor is rebuilt with [`Monitor::keyed`],
+/// so it gets `C::COUNT` conditions and no name or level; with the default
+/// `usize` keys that means no conditions at all, whatever
+/// [`Monitor::with_conditions`] was given. Deserialize the data on its own and
+/// build the monitor from it where those matter.
+#[cfg(feature = "serde")]
+impl<'de, T, C, R> serde::Deserialize<'de> for Monitor<T, C, R>
+where
+    T: serde::Deserialize<'de>,
+    C: ConditionKey,
+    R: lock_api::RawMutex,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Monitor::keyed)
+    }
+}
+
+impl<T: fmt::Debug, C, R: lock_api::RawMutex> fmt::Debug for Monitor<T, C, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        struct LockedPlaceholder;
+        impl fmt::Debug for LockedPlaceholder {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("<locked>")
+            }
+        }
+
+        let waiting: Vec<_> = self
+            .waiting
+            .iter()
+            .map(|w| w.load(Ordering::Relaxed))
+            .collect();
+
+        let mut d = f.debug_struct("Monitor");
+        if let Some(name) = self.name {
+            d.field("name", &name);
+        }
+        if self.raw_mutex().try_lock() {
+            let _peek = Peek(self.raw_mutex());
+            d.field("data", unsafe { &*self.mutex.data_ptr() })
+                .field("locked", &false);
+        } else {
+            d.field("data", &LockedPlaceholder).field("locked", &true);
+        }
+        d.field("waiters", &waiting[0]);
+        if self.conditions > 0 {
+            d.field("condition_waiters", &&waiting[1..]);
+        }
+        d.finish()
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SignalMode {
+    #[default]
+    Continue,
+    Wait,
+    UrgentWait,
+}
+
+// Raw mutexes that may be unlocked by a thread other than the one that locked
+// them, which the Hoare signal modes rely on to hand the lock to a waiter.
+// `lock_api` has no way to say so: `GuardSend` comes closest, but parking_lot's
+// own mutexes only claim it with their `send_guard` feature.
+/// # Safety
+///
+/// Unlocking from any thread must be sound while the mutex is locked.
+pub unsafe trait RawMutexHandoff: lock_api::RawMutex {}
+
+#[cfg(feature = "std")]
+unsafe impl RawMutexHandoff for parking_lot::RawMutex {}
+
+#[cfg(feature = "std")]
+unsafe impl RawMutexHandoff for parking_lot::RawFairMutex {}
+
+unsafe impl RawMutexHandoff for RawSpinMutex {}
+
+pub struct MonitorGuard<'a, T, C = usize, R: lock_api::RawMutex = RawMutex> {
+    monitor: &'a Monitor<T, C, R>,
+    mode: SignalMode,
+    _guard: PhantomData<lock_api::MutexGuard<'a, R, T>>,
+}
+
+impl<'a, T, C: ConditionKey, R: lock_api::RawMutex> MonitorGuard<'a, T, C, R> {
+    fn new(monitor: &'a Monitor<T, C, R>) -> Self {
+        MonitorGuard {
+            monitor,
+            mode: SignalMode::Continue,
+            _guard: PhantomData,
+        }
+    }
+
+    fn condition(&self, cond: C) -> usize {
+        self.monitor.condition(cond)
+    }
+
+    pub fn signal_mode(&self) -> SignalMode {
+        self.mode
+    }
+
+    #[track_caller]
+    pub fn notify_one(&mut self) -> usize {
+        self.signal(0)
+    }
+
+    #[track_caller]
+    pub fn notify_n(&mut self, n: usize) -> usize {
+        self.broadcast(0, n)
+    }
+
+    #[track_caller]
+    pub fn notify_all(&mut self) -> usize {
+        self.broadcast(0, usize::MAX)
+    }
+
+    #[track_caller]
+    pub fn wait(&mut self) -> WaitOutcome {
+        self.park_until(Park::on(0), None)
+    }
+
+    #[track_caller]
+    pub fn wait_for(&mut self, timeout: Duration) -> WaitOutcome {
+        self.park_until(Park::on(0), deadline(timeout))
+    }
+
+    #[track_caller]
+    pub fn wait_until(&mut self, timeout: Instant) -> WaitOutcome {
+        self.park_until(Park::on(0), Some(timeout))
+    }
+
+    #[track_caller]
+    pub fn wait_while<F>(&mut self, condition: F) -> WaitOutcome
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.park_while(Park::on(0), None, condition)
+    }
+
+    #[track_caller]
+    pub fn wait_while_for<F>(&mut self, timeout: Duration, condition: F) -> WaitOutcome
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.park_while(Park::on(0), deadline(timeout), condition)
+    }
+
+    #[track_caller]
+    pub fn wait_while_until<F>(&mut self, timeout: Instant, condition: F) -> WaitOutcome
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.park_while(Park::on(0), Some(timeout), condition)
+    }
+
+    #[track_caller]
+    pub fn notify_one_on(&mut self, cond: C) -> usize {
+        self.signal(self.condition(cond))
+    }
+
+    #[track_caller]
+    pub fn notify_n_on(&mut self, cond: C, n: usize) -> usize {
+        self.broadcast(self.condition(cond), n)
+    }
+
+    #[track_caller]
+    pub fn notify_all_on(&mut self, cond: C) -> usize {
+        self.broadcast(self.condition(cond), usize::MAX)
+    }
+
+    #[track_caller]
+    pub fn wait_on(&mut self, cond: C) -> WaitOutcome {
+        self.park_until(Park::on(self.condition(cond)), None)
+    }
+
+    #[track_caller]
+    pub fn wait_on_for(&mut self, cond: C, timeout: Duration) -> WaitOutcome {
+        self.park_until(Park::on(self.condition(cond)), deadline(timeout))
+    }
+
+    #[track_caller]
+    pub fn wait_on_until(&mut self, cond: C, timeout: Instant) -> WaitOutcome {
+        self.park_until(Park::on(self.condition(cond)), Some(timeout))
+    }
+
+    #[track_caller]
+    pub fn wait_while_on<F>(&mut self, cond: C, condition: F) -> WaitOutcome
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.park_while(Park::on(self.condition(cond)), None, condition)
+    }
+
+    #[track_caller]
+    pub fn wait_while_on_for<F>(&mut self, cond: C, timeout: Duration, condition: F) -> WaitOutcome
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.park_while(Park::on(self.condition(cond)), deadline(timeout), condition)
+    }
+
+    #[track_caller]
+    pub fn wait_while_on_until<F>(&mut self, cond: C, timeout: Instant, condition: F) -> WaitOutcome
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.park_while(Park::on(self.condition(cond)), Some(timeout), condition)
+    }
+
+    #[track_caller]
+    pub fn wait_with_priority(&mut self, priority: i32) -> WaitOutcome {
+        self.park_until(Park::on(0).priority(priority), None)
+    }
+
+    #[track_caller]
+    pub fn wait_with_priority_for(&mut self, priority: i32, timeout: Duration) -> WaitOutcome {
+        self.park_until(Park::on(0).priority(priority), deadline(timeout))
+    }
+
+    #[track_caller]
+    pub fn wait_with_priority_until(&mut self, priority: i32, timeout: Instant) -> WaitOutcome {
+        self.park_until(Park::on(0).priority(priority), Some(timeout))
+    }
+
+    #[track_caller]
+    pub fn wait_while_with_priority<F>(&mut self, priority: i32, condition: F) -> WaitOutcome
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.park_while(Park::on(0).priority(priority), None, condition)
+    }
+
+    #[track_caller]
+    pub fn wait_on_with_priority(&mut self, cond: C, priority: i32) -> WaitOutcome {
+        self.park_until(Park::on(self.condition(cond)).priority(priority), None)
+    }
+
+    #[track_caller]
+    pub fn wait_while_on_with_priority<F>(
+        &mut self,
+        cond: C,
+        priority: i32,
+        condition: F,
+    ) -> WaitOutcome
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        let park = Park::on(self.condition(cond)).priority(priority);
+        self.park_while(park, None, condition)
+    }
+
+    #[track_caller]
+    pub fn wait_cancellable(&mut self, token: &CancellationToken) -> WaitOutcome {
+        self.park_until(Park::on(0).token(token), None)
+    }
+
+    #[track_caller]
+    pub fn wait_cancellable_for(
+        &mut self,
+        token: &CancellationToken,
+        timeout: Duration,
+    ) -> WaitOutcome {
+        self.park_until(Park::on(0).token(token), deadline(timeout))
+    }
+
+    #[track_caller]
+    pub fn wait_cancellable_until(
+        &mut self,
+        token: &CancellationToken,
+        timeout: Instant,
+    ) -> WaitOutcome {
+        self.park_until(Park::on(0).token(token), Some(timeout))
+    }
+
+    #[track_caller]
+    pub fn wait_while_cancellable<F>(
+        &mut self,
+        token: &CancellationToken,
+        condition: F,
+    ) -> WaitOutcome
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.park_while(Park::on(0).token(token), None, condition)
+    }
+
+    #[track_caller]
+    pub fn wait_while_cancellable_for<F>(
+        &mut self,
+        token: &CancellationToken,
+        timeout: Duration,
+        condition: F,
+    ) -> WaitOutcome
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.park_while(Park::on(0).token(token), deadline(timeout), condition)
+    }
+
+    #[track_caller]
+    pub fn wait_while_cancellable_until<F>(
+        &mut self,
+        token: &CancellationToken,
+        timeout: Instant,
+        condition: F,
+    ) -> WaitOutcome
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.park_while(Park::on(0).token(token), Some(timeout), condition)
+    }
+
+    #[track_caller]
+    pub fn wait_on_cancellable(&mut self, cond: C, token: &CancellationToken) -> WaitOutcome {
+        self.park_until(Park::on(self.condition(cond)).token(token), None)
+    }
+
+    #[track_caller]
+    pub fn wait_while_on_cancellable<F>(
+        &mut self,
+        cond: C,
+        token: &CancellationToken,
+        condition: F,
+    ) -> WaitOutcome
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.park_while(Park::on(self.condition(cond)).token(token), None, condition)
+    }
+
+    // Wakes every thread waiting on the monitor, under any condition, and
+    // makes every later wait return at once. Returns how many were woken.
+    // This is the only way a wait ends with `WaitOutcome::Closed`, for shutting
+    // down consumers that would otherwise wait forever. Closing cannot be
+    // undone, and a plain `wait` in a loop around a condition that is never
+    // met then spins: such loops must stop on `Closed` or `is_closed`. The
+    // predicate waits return `Closed` themselves.
+    pub fn close(&mut self) -> usize {
+        let monitor = self.monitor;
+        monitor.closed.store(true, Ordering::Release);
+        unsafe { monitor.with_state(|s| s.queues.iter_mut().map(WaitQueue::close).sum()) }
+    }
+
+    pub fn map<U, F>(self, mut f: F) -> MappedMonitorGuard<'a, T, U, C, R>
+    where
+        U: ?Sized,
+        F: FnMut(&mut T) -> &mut U + 'a,
+    {
+        let project = mapped::projection(move |t| Some(f(t)));
+        match MappedMonitorGuard::new(self, Box::new(project)) {
+            Ok(mapped) => mapped,
+            Err(_) => unreachable!("infallible projection failed"),
+        }
+    }
+
+    pub fn try_map<U, F>(self, f: F) -> Result<MappedMonitorGuard<'a, T, U, C, R>, Self>
+    where
+        U: ?Sized,
+        F: FnMut(&mut T) -> Option<&mut U> + 'a,
+    {
+        MappedMonitorGuard::new(self, Box::new(f))
+    }
+
+    /// Releases and reacquires the lock. Urgent signallers get it first, but
+    /// with an unfair raw mutex such as parking_lot's this thread usually
+    /// takes it straight back; use [`bump_fair`](Self::bump_fair) to yield to
+    /// queued lockers.
+    pub fn bump(&mut self) {
+        unsafe { self.monitor.release() };
+        self.monitor.acquire();
+    }
+
+    #[track_caller]
+    pub fn unlocked<F, U>(&mut self, f: F) -> U
+    where
+        F: FnOnce() -> U,
+    {
+        let monitor = self.monitor;
+        order::released(monitor.key(), monitor.level);
+        unsafe { monitor.release() };
+        let relock = Relock(monitor);
+        let result = f();
+        order::acquiring(monitor.level);
+        drop(relock);
+        order::acquired(monitor.key(), monitor.level);
+        result
+    }
+
+    #[track_caller]
+    fn signal(&mut self, queue: usize) -> usize {
+        let monitor = self.monitor;
+        let notified = match self.mode {
+            SignalMode::Continue => unsafe { monitor.with_state(|s| s.queues[queue].notify(1)) },
+            SignalMode::Wait => match unsafe { monitor.with_state(|s| s.queues[queue].select()) } {
+                Some(waiter) if waiter.accepts_handoff() => {
+                    unsafe { monitor.hand_off(&waiter) };
+                    monitor.acquire();
+                    1
+                }
+                Some(waiter) => {
+                    waiter.complete_notify();
+                    1
+                }
+                None => 0,
+            },
+            SignalMode::UrgentWait => {
+                let signaller = Waiter::new();
+                let selected = unsafe {
+                    monitor.with_state(|s| {
+                        let selected = s.queues[queue].select();
+                        if selected.as_ref().is_some_and(|w| w.accepts_handoff()) {
+                            s.urgent.push(signaller.clone(), 0);
+                        }
+                        selected
+                    })
+                };
+                match selected {
+                    Some(waiter) if waiter.accepts_handoff() => {
+                        unsafe { monitor.hand_off(&waiter) };
+                        signaller.park(None);
+                        monitor.received();
+                        1
+                    }
+                    Some(waiter) => {
+                        waiter.complete_notify();
+                        1
+                    }
+                    None => 0,
+                }
+            }
+        };
+        monitor.stats.notified(notified);
+        trace::notified(monitor.name, monitor.key(), queue, notified);
+        notified
+    }
+
+    #[track_caller]
+    fn broadcast(&mut self, queue: usize, n: usize) -> usize {
+        let monitor = self.monitor;
+        let notified = unsafe { monitor.with_state(|s| s.queues[queue].notify(n)) };
+        monitor.stats.notified(notified);
+        trace::notified(monitor.name, monitor.key(), queue, notified);
+        notified
+    }
+
+    fn park(&mut self, park: Park<'_>, deadline: Option<Instant>) -> Wakeup {
+        let monitor = self.monitor;
+        monitor.stats.waited();
+        if monitor.is_closed() {
+            return Wakeup::Closed;
+        }
+        let waiter = Waiter::new();
+        if let Some(token) = park.token {
+            if !token.register(&waiter) {
+                return Wakeup::Cancelled;
+            }
+        }
+
+        monitor.waiting[park.queue].fetch_add(1, Ordering::Relaxed);
+        unsafe {
+            monitor.with_state(|s| s.queues[park.queue].push(Arc::clone(&waiter), park.priority));
+            monitor.release();
+        }
+
+        let wakeup = waiter.park(deadline);
+        monitor.waiting[park.queue].fetch_sub(1, Ordering::Relaxed);
+        match wakeup {
+            Wakeup::Handoff => monitor.received(),
+            Wakeup::Notified | Wakeup::Closed => monitor.acquire(),
+            Wakeup::TimedOut | Wakeup::Cancelled => {
+                monitor.acquire();
+                unsafe { monitor.with_state(|s| s.queues[park.queue].remove(&waiter)) };
+            }
+        }
+
+        if let Some(token) = park.token {
+            token.unregister(&waiter);
+        }
+        wakeup
+    }
+
+    #[track_caller]
+    fn park_until(&mut self, park: Park<'_>, deadline: Option<Instant>) -> WaitOutcome {
+        let trace = trace::waiting(self.monitor.name, self.monitor.key(), park.queue);
+        let outcome = WaitOutcome::new(self.park(park, deadline), deadline);
+        trace.woke(&outcome);
+        outcome
+    }
+
+    #[track_caller]
+    fn park_while<F>(
+        &mut self,
+        park: Park<'_>,
+        deadline: Option<Instant>,
+        condition: F,
+    ) -> WaitOutcome
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        let trace = trace::waiting(self.monitor.name, self.monitor.key(), park.queue);
+        let outcome = self.wait_loop(park, deadline, condition);
+        trace.woke(&outcome);
+        outcome
+    }
+
+    fn wait_loop<F>(
+        &mut self,
+        park: Park<'_>,
+        deadline: Option<Instant>,
+        mut condition: F,
+    ) -> WaitOutcome
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        let mut woken = false;
+        while condition(self) {
+            if woken {
+                self.monitor.stats.spurious();
+            }
+            // The predicate gets one last look after the deadline, a
+            // cancellation or the monitor closing so that a notification
+            // racing with any of them is not reported as a failed wait.
+            match self.park(park, deadline) {
+                wakeup @ (Wakeup::TimedOut | Wakeup::Cancelled | Wakeup::Closed)
+                    if condition(self) =>
+                {
+                    return WaitOutcome::new(wakeup, deadline)
+                }
+                _ => woken = true,
+            }
+        }
+        WaitOutcome::satisfied(deadline)
     }
 }
 
-pub struct MonitorGuard<'a, T> {
-    cv: &'a Condvar,
-    guard: MutexGuard<'a, T>,
+// Takes the lock back even if the closure passed to `unlocked` panics, so the
+// guard never releases a lock it does not own.
+struct Relock<'a, T, C, R: lock_api::RawMutex>(&'a Monitor<T, C, R>);
+
+impl<T, C, R: lock_api::RawMutex> Drop for Relock<'_, T, C, R> {
+    fn drop(&mut self) {
+        self.0.acquire();
+    }
 }
 
-impl<'a, T> MonitorGuard<'a, T> {
-    pub fn new(cv: &'a Condvar, guard: MutexGuard<'a, T>) -> Self {
-        MonitorGuard { cv, guard }
+// Unlocks the lock `Debug` took to look at the data, even if formatting it
+// panics. The look is not an acquisition as far as `stats` and deadlock
+// detection are concerned, and there is never an urgent signaller to hand a
+// lock over to while it was free.
+struct Peek<'a, R: lock_api::RawMutex>(&'a R);
+
+impl<R: lock_api::RawMutex> Drop for Peek<'_, R> {
+    fn drop(&mut self) {
+        unsafe { self.0.unlock() };
     }
+}
+
+#[derive(Clone, Copy)]
+struct Park<'t> {
+    queue: usize,
+    priority: i32,
+    token: Option<&'t CancellationToken>,
+}
 
-    pub fn notify_one(&self) {
-        self.cv.notify_one();
+impl<'t> Park<'t> {
+    fn on(queue: usize) -> Self {
+        Park {
+            queue,
+            priority: 0,
+            token: None,
+        }
     }
 
-    pub fn notify_all(&self) {
-        self.cv.notify_all();
+    fn priority(self, priority: i32) -> Self {
+        Park { priority, ..self }
     }
 
-    pub fn wait(&mut self) {
-        self.cv.wait(&mut self.guard);
+    fn token(self, token: &'t CancellationToken) -> Self {
+        Park {
+            token: Some(token),
+            ..self
+        }
+    }
+}
+
+// Timeouts too long to be represented are treated as no timeout at all.
+fn deadline(timeout: Duration) -> Option<Instant> {
+    Instant::now().checked_add(timeout)
+}
+
+impl<'a, T, C: ConditionKey, R: RawMutexFair> MonitorGuard<'a, T, C, R> {
+    pub fn unlock_fair(self) {
+        let monitor = self.monitor;
+        mem::forget(self);
+        order::released(monitor.key(), monitor.level);
+        unsafe { monitor.release_fair() };
     }
 
-    pub fn wait_for(&mut self, timeout: Duration) -> WaitTimeoutResult {
-        self.cv.wait_for(&mut self.guard, timeout)
+    pub fn bump_fair(&mut self) {
+        unsafe { self.monitor.bump_fair() };
     }
+}
+
+impl<T, C, R: RawMutexHandoff> MonitorGuard<'_, T, C, R> {
+    pub fn set_signal_mode(&mut self, mode: SignalMode) {
+        self.mode = mode;
+    }
+}
 
-    pub fn wait_until(&mut self, timeout: Instant) -> WaitTimeoutResult {
-        self.cv.wait_until(&mut self.guard, timeout)
+impl<T, C, R: lock_api::RawMutex> Drop for MonitorGuard<'_, T, C, R> {
+    fn drop(&mut self) {
+        order::released(self.monitor.key(), self.monitor.level);
+        unsafe { self.monitor.release() };
     }
 }
 
-impl<T> Deref for MonitorGuard<'_, T> {
+impl<T, C, R: lock_api::RawMutex> Deref for MonitorGuard<'_, T, C, R> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
-        self.guard.deref()
+        unsafe { &*self.monitor.mutex.data_ptr() }
     }
 }
 
-impl<T> DerefMut for MonitorGuard<'_, T> {
+impl<T, C, R: lock_api::RawMutex> DerefMut for MonitorGuard<'_, T, C, R> {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        self.guard.deref_mut()
+        unsafe { &mut *self.monitor.mutex.data_ptr() }
     }
 }
@@ -0,0 +1,446 @@
+use crate::{
+    CancellationToken, ConditionKey, Instant, MonitorGuard, RawMutex, RawMutexHandoff, SignalMode,
+    WaitOutcome,
+};
+use alloc::boxed::Box;
+use core::{
+    ops::{Deref, DerefMut},
+    time::Duration,
+};
+
+type Projection<'a, T, U> = dyn FnMut(&mut T) -> Option<&mut U> + 'a;
+
+// The projection is kept and re-applied whenever the lock has been given up,
+// since whatever it points into may have moved in the meantime. That includes
+// a notify in one of the Hoare modes, which lends the lock to the woken
+// waiter. Waits and notifies therefore consume the guard and hand it back
+// alongside their result, or hand back the plain guard if a `try_map`
+// projection no longer applies. A predicate wait stops waiting as soon as the
+// projection fails.
+pub struct MappedMonitorGuard<'a, T, U: ?Sized, C = usize, R: lock_api::RawMutex = RawMutex> {
+    guard: MonitorGuard<'a, T, C, R>,
+    project: Box<Projection<'a, T, U>>,
+    data: *mut U,
+}
+
+/// A mapped guard after a wait or notify, or the plain guard if the projection
+/// failed once the lock was back.
+pub type Remapped<'a, T, U, C = usize, R = RawMutex> =
+    Result<MappedMonitorGuard<'a, T, U, C, R>, MonitorGuard<'a, T, C, R>>;
+
+// Pins down the higher-ranked signature closures need to be boxed as a
+// `Projection`.
+pub(crate) fn projection<T, U: ?Sized, F>(f: F) -> F
+where
+    F: FnMut(&mut T) -> Option<&mut U>,
+{
+    f
+}
+
+impl<'a, T, U: ?Sized, C: ConditionKey, R: lock_api::RawMutex> MappedMonitorGuard<'a, T, U, C, R> {
+    pub(crate) fn new(
+        mut guard: MonitorGuard<'a, T, C, R>,
+        mut project: Box<Projection<'a, T, U>>,
+    ) -> Result<Self, MonitorGuard<'a, T, C, R>> {
+        let data: *mut U = match project(&mut guard) {
+            Some(data) => data,
+            None => return Err(guard),
+        };
+        Ok(MappedMonitorGuard {
+            guard,
+            project,
+            data,
+        })
+    }
+
+    // Re-applies the projection once the lock is back, handing back the plain
+    // guard if it no longer applies.
+    fn remap(mut self) -> Remapped<'a, T, U, C, R> {
+        match (self.project)(&mut self.guard).map(|data| data as *mut U) {
+            Some(data) => {
+                self.data = data;
+                Ok(self)
+            }
+            None => Err(self.guard),
+        }
+    }
+
+    pub fn signal_mode(&self) -> SignalMode {
+        self.guard.signal_mode()
+    }
+
+    #[track_caller]
+    pub fn notify_one(mut self) -> (usize, Remapped<'a, T, U, C, R>) {
+        let result = self.guard.notify_one();
+        (result, self.remap())
+    }
+
+    #[track_caller]
+    pub fn notify_n(mut self, n: usize) -> (usize, Remapped<'a, T, U, C, R>) {
+        let result = self.guard.notify_n(n);
+        (result, self.remap())
+    }
+
+    #[track_caller]
+    pub fn notify_all(mut self) -> (usize, Remapped<'a, T, U, C, R>) {
+        let result = self.guard.notify_all();
+        (result, self.remap())
+    }
+
+    #[track_caller]
+    pub fn notify_one_on(mut self, cond: C) -> (usize, Remapped<'a, T, U, C, R>) {
+        let result = self.guard.notify_one_on(cond);
+        (result, self.remap())
+    }
+
+    #[track_caller]
+    pub fn notify_n_on(mut self, cond: C, n: usize) -> (usize, Remapped<'a, T, U, C, R>) {
+        let result = self.guard.notify_n_on(cond, n);
+        (result, self.remap())
+    }
+
+    #[track_caller]
+    pub fn notify_all_on(mut self, cond: C) -> (usize, Remapped<'a, T, U, C, R>) {
+        let result = self.guard.notify_all_on(cond);
+        (result, self.remap())
+    }
+
+    pub fn close(&mut self) -> usize {
+        self.guard.close()
+    }
+
+    #[track_caller]
+    pub fn wait(mut self) -> (WaitOutcome, Remapped<'a, T, U, C, R>) {
+        let result = self.guard.wait();
+        (result, self.remap())
+    }
+
+    #[track_caller]
+    pub fn wait_for(mut self, timeout: Duration) -> (WaitOutcome, Remapped<'a, T, U, C, R>) {
+        let result = self.guard.wait_for(timeout);
+        (result, self.remap())
+    }
+
+    #[track_caller]
+    pub fn wait_until(mut self, timeout: Instant) -> (WaitOutcome, Remapped<'a, T, U, C, R>) {
+        let result = self.guard.wait_until(timeout);
+        (result, self.remap())
+    }
+
+    #[track_caller]
+    pub fn wait_while<F>(mut self, mut condition: F) -> (WaitOutcome, Remapped<'a, T, U, C, R>)
+    where
+        F: FnMut(&mut U) -> bool,
+    {
+        let project = &mut *self.project;
+        let result = self
+            .guard
+            .wait_while(|t| project(t).is_some_and(&mut condition));
+        (result, self.remap())
+    }
+
+    #[track_caller]
+    pub fn wait_while_for<F>(
+        mut self,
+        timeout: Duration,
+        mut condition: F,
+    ) -> (WaitOutcome, Remapped<'a, T, U, C, R>)
+    where
+        F: FnMut(&mut U) -> bool,
+    {
+        let project = &mut *self.project;
+        let result = self
+            .guard
+            .wait_while_for(timeout, |t| project(t).is_some_and(&mut condition));
+        (result, self.remap())
+    }
+
+    #[track_caller]
+    pub fn wait_while_until<F>(
+        mut self,
+        timeout: Instant,
+        mut condition: F,
+    ) -> (WaitOutcome, Remapped<'a, T, U, C, R>)
+    where
+        F: FnMut(&mut U) -> bool,
+    {
+        let project = &mut *self.project;
+        let result = self
+            .guard
+            .wait_while_until(timeout, |t| project(t).is_some_and(&mut condition));
+        (result, self.remap())
+    }
+
+    #[track_caller]
+    pub fn wait_on(mut self, cond: C) -> (WaitOutcome, Remapped<'a, T, U, C, R>) {
+        let result = self.guard.wait_on(cond);
+        (result, self.remap())
+    }
+
+    #[track_caller]
+    pub fn wait_on_for(
+        mut self,
+        cond: C,
+        timeout: Duration,
+    ) -> (WaitOutcome, Remapped<'a, T, U, C, R>) {
+        let result = self.guard.wait_on_for(cond, timeout);
+        (result, self.remap())
+    }
+
+    #[track_caller]
+    pub fn wait_on_until(
+        mut self,
+        cond: C,
+        timeout: Instant,
+    ) -> (WaitOutcome, Remapped<'a, T, U, C, R>) {
+        let result = self.guard.wait_on_until(cond, timeout);
+        (result, self.remap())
+    }
+
+    #[track_caller]
+    pub fn wait_while_on<F>(
+        mut self,
+        cond: C,
+        mut condition: F,
+    ) -> (WaitOutcome, Remapped<'a, T, U, C, R>)
+    where
+        F: FnMut(&mut U) -> bool,
+    {
+        let project = &mut *self.project;
+        let result = self
+            .guard
+            .wait_while_on(cond, |t| project(t).is_some_and(&mut condition));
+        (result, self.remap())
+    }
+
+    #[track_caller]
+    pub fn wait_while_on_for<F>(
+        mut self,
+        cond: C,
+        timeout: Duration,
+        mut condition: F,
+    ) -> (WaitOutcome, Remapped<'a, T, U, C, R>)
+    where
+        F: FnMut(&mut U) -> bool,
+    {
+        let project = &mut *self.project;
+        let result = self
+            .guard
+            .wait_while_on_for(cond, timeout, |t| project(t).is_some_and(&mut condition));
+        (result, self.remap())
+    }
+
+    #[track_caller]
+    pub fn wait_while_on_until<F>(
+        mut self,
+        cond: C,
+        timeout: Instant,
+        mut condition: F,
+    ) -> (WaitOutcome, Remapped<'a, T, U, C, R>)
+    where
+        F: FnMut(&mut U) -> bool,
+    {
+        let project = &mut *self.project;
+        let result = self
+            .guard
+            .wait_while_on_until(cond, timeout, |t| project(t).is_some_and(&mut condition));
+        (result, self.remap())
+    }
+
+    #[track_caller]
+    pub fn wait_with_priority(mut self, priority: i32) -> (WaitOutcome, Remapped<'a, T, U, C, R>) {
+        let result = self.guard.wait_with_priority(priority);
+        (result, self.remap())
+    }
+
+    #[track_caller]
+    pub fn wait_with_priority_for(
+        mut self,
+        priority: i32,
+        timeout: Duration,
+    ) -> (WaitOutcome, Remapped<'a, T, U, C, R>) {
+        let result = self.guard.wait_with_priority_for(priority, timeout);
+        (result, self.remap())
+    }
+
+    #[track_caller]
+    pub fn wait_with_priority_until(
+        mut self,
+        priority: i32,
+        timeout: Instant,
+    ) -> (WaitOutcome, Remapped<'a, T, U, C, R>) {
+        let result = self.guard.wait_with_priority_until(priority, timeout);
+        (result, self.remap())
+    }
+
+    #[track_caller]
+    pub fn wait_while_with_priority<F>(
+        mut self,
+        priority: i32,
+        mut condition: F,
+    ) -> (WaitOutcome, Remapped<'a, T, U, C, R>)
+    where
+        F: FnMut(&mut U) -> bool,
+    {
+        let project = &mut *self.project;
+        let result = self
+            .guard
+            .wait_while_with_priority(priority, |t| project(t).is_some_and(&mut condition));
+        (result, self.remap())
+    }
+
+    #[track_caller]
+    pub fn wait_on_with_priority(
+        mut self,
+        cond: C,
+        priority: i32,
+    ) -> (WaitOutcome, Remapped<'a, T, U, C, R>) {
+        let result = self.guard.wait_on_with_priority(cond, priority);
+        (result, self.remap())
+    }
+
+    #[track_caller]
+    pub fn wait_while_on_with_priority<F>(
+        mut self,
+        cond: C,
+        priority: i32,
+        mut condition: F,
+    ) -> (WaitOutcome, Remapped<'a, T, U, C, R>)
+    where
+        F: FnMut(&mut U) -> bool,
+    {
+        let project = &mut *self.project;
+        let result = self.guard.wait_while_on_with_priority(cond, priority, |t| {
+            project(t).is_some_and(&mut condition)
+        });
+        (result, self.remap())
+    }
+
+    #[track_caller]
+    pub fn wait_cancellable(
+        mut self,
+        token: &CancellationToken,
+    ) -> (WaitOutcome, Remapped<'a, T, U, C, R>) {
+        let result = self.guard.wait_cancellable(token);
+        (result, self.remap())
+    }
+
+    #[track_caller]
+    pub fn wait_cancellable_for(
+        mut self,
+        token: &CancellationToken,
+        timeout: Duration,
+    ) -> (WaitOutcome, Remapped<'a, T, U, C, R>) {
+        let result = self.guard.wait_cancellable_for(token, timeout);
+        (result, self.remap())
+    }
+
+    #[track_caller]
+    pub fn wait_cancellable_until(
+        mut self,
+        token: &CancellationToken,
+        timeout: Instant,
+    ) -> (WaitOutcome, Remapped<'a, T, U, C, R>) {
+        let result = self.guard.wait_cancellable_until(token, timeout);
+        (result, self.remap())
+    }
+
+    #[track_caller]
+    pub fn wait_while_cancellable<F>(
+        mut self,
+        token: &CancellationToken,
+        mut condition: F,
+    ) -> (WaitOutcome, Remapped<'a, T, U, C, R>)
+    where
+        F: FnMut(&mut U) -> bool,
+    {
+        let project = &mut *self.project;
+        let result = self
+            .guard
+            .wait_while_cancellable(token, |t| project(t).is_some_and(&mut condition));
+        (result, self.remap())
+    }
+
+    #[track_caller]
+    pub fn wait_while_cancellable_for<F>(
+        mut self,
+        token: &CancellationToken,
+        timeout: Duration,
+        mut condition: F,
+    ) -> (WaitOutcome, Remapped<'a, T, U, C, R>)
+    where
+        F: FnMut(&mut U) -> bool,
+    {
+        let project = &mut *self.project;
+        let result = self
+            .guard
+            .wait_while_cancellable_for(token, timeout, |t| project(t).is_some_and(&mut condition));
+        (result, self.remap())
+    }
+
+    #[track_caller]
+    pub fn wait_while_cancellable_until<F>(
+        mut self,
+        token: &CancellationToken,
+        timeout: Instant,
+        mut condition: F,
+    ) -> (WaitOutcome, Remapped<'a, T, U, C, R>)
+    where
+        F: FnMut(&mut U) -> bool,
+    {
+        let project = &mut *self.project;
+        let result = self
+            .guard
+            .wait_while_cancellable_until(token, timeout, |t| {
+                project(t).is_some_and(&mut condition)
+            });
+        (result, self.remap())
+    }
+
+    #[track_caller]
+    pub fn wait_on_cancellable(
+        mut self,
+        cond: C,
+        token: &CancellationToken,
+    ) -> (WaitOutcome, Remapped<'a, T, U, C, R>) {
+        let result = self.guard.wait_on_cancellable(cond, token);
+        (result, self.remap())
+    }
+
+    #[track_caller]
+    pub fn wait_while_on_cancellable<F>(
+        mut self,
+        cond: C,
+        token: &CancellationToken,
+        mut condition: F,
+    ) -> (WaitOutcome, Remapped<'a, T, U, C, R>)
+    where
+        F: FnMut(&mut U) -> bool,
+    {
+        let project = &mut *self.project;
+        let result = self
+            .guard
+            .wait_while_on_cancellable(cond, token, |t| project(t).is_some_and(&mut condition));
+        (result, self.remap())
+    }
+}
+
+impl<T, U: ?Sized, C, R: RawMutexHandoff> MappedMonitorGuard<'_, T, U, C, R> {
+    pub fn set_signal_mode(&mut self, mode: SignalMode) {
+        self.guard.set_signal_mode(mode);
+    }
+}
+
+impl<T, U: ?Sized, C, R: lock_api::RawMutex> Deref for MappedMonitorGuard<'_, T, U, C, R> {
+    type Target = U;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.data }
+    }
+}
+
+impl<T, U: ?Sized, C, R: lock_api::RawMutex> DerefMut for MappedMonitorGuard<'_, T, U, C, R> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.data }
+    }
+}
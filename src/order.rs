@@ -0,0 +1,72 @@
+// Lock-ordering checks for monitors built with `Monitor::leveled`. A
+// thread may only lock such a monitor while every leveled monitor it already
+// holds has a strictly lower level. Only debug builds with the `std` feature
+// keep track; otherwise every hook is a no-op.
+
+#[cfg(all(debug_assertions, feature = "std"))]
+pub(crate) use enabled::*;
+
+#[cfg(not(all(debug_assertions, feature = "std")))]
+pub(crate) use disabled::*;
+
+#[cfg(all(debug_assertions, feature = "std"))]
+mod enabled {
+    use std::{cell::RefCell, panic::Location};
+
+    struct Held {
+        key: usize,
+        level: u32,
+        site: &'static Location<'static>,
+    }
+
+    thread_local! {
+        static HELD: RefCell<Vec<Held>> = const { RefCell::new(Vec::new()) };
+    }
+
+    #[track_caller]
+    pub(crate) fn acquiring(level: Option<u32>) {
+        let Some(level) = level else { return };
+        let site = Location::caller();
+        HELD.with(|held| {
+            let held = held.borrow();
+            if let Some(h) = held.iter().rev().find(|h| h.level >= level) {
+                panic!(
+                    "lock order violation: monitor at level {} locked at {} \
+                     while holding monitor at level {} locked at {}",
+                    level, site, h.level, h.site
+                );
+            }
+        });
+    }
+
+    #[track_caller]
+    pub(crate) fn acquired(key: usize, level: Option<u32>) {
+        let Some(level) = level else { return };
+        let site = Location::caller();
+        HELD.with(|held| held.borrow_mut().push(Held { key, level, site }));
+    }
+
+    pub(crate) fn released(key: usize, level: Option<u32>) {
+        if level.is_none() {
+            return;
+        }
+        let _ = HELD.try_with(|held| {
+            let mut held = held.borrow_mut();
+            if let Some(i) = held.iter().rposition(|h| h.key == key) {
+                held.remove(i);
+            }
+        });
+    }
+}
+
+#[cfg(not(all(debug_assertions, feature = "std")))]
+mod disabled {
+    #[inline]
+    pub(crate) fn acquiring(_level: Option<u32>) {}
+
+    #[inline]
+    pub(crate) fn acquired(_key: usize, _level: Option<u32>) {}
+
+    #[inline]
+    pub(crate) fn released(_key: usize, _level: Option<u32>) {}
+}
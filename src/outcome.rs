@@ -0,0 +1,57 @@
+use crate::{waiter::Wakeup, Instant};
+use core::time::Duration;
+
+// `remaining` is what is left of the wait's deadline when it returned, or
+// `None` for waits without one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaitOutcome {
+    Notified { remaining: Option<Duration> },
+    TimedOut,
+    Cancelled { remaining: Option<Duration> },
+    Closed { remaining: Option<Duration> },
+    PredicateSatisfied { remaining: Option<Duration> },
+}
+
+impl WaitOutcome {
+    pub(crate) fn new(wakeup: Wakeup, deadline: Option<Instant>) -> Self {
+        let remaining = remaining(deadline);
+        match wakeup {
+            Wakeup::Notified | Wakeup::Handoff => WaitOutcome::Notified { remaining },
+            Wakeup::TimedOut => WaitOutcome::TimedOut,
+            Wakeup::Cancelled => WaitOutcome::Cancelled { remaining },
+            Wakeup::Closed => WaitOutcome::Closed { remaining },
+        }
+    }
+
+    pub(crate) fn satisfied(deadline: Option<Instant>) -> Self {
+        WaitOutcome::PredicateSatisfied {
+            remaining: remaining(deadline),
+        }
+    }
+
+    pub fn timed_out(&self) -> bool {
+        matches!(self, WaitOutcome::TimedOut)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, WaitOutcome::Cancelled { .. })
+    }
+
+    pub fn is_closed(&self) -> bool {
+        matches!(self, WaitOutcome::Closed { .. })
+    }
+
+    pub fn remaining(&self) -> Option<Duration> {
+        match *self {
+            WaitOutcome::Notified { remaining }
+            | WaitOutcome::Cancelled { remaining }
+            | WaitOutcome::Closed { remaining }
+            | WaitOutcome::PredicateSatisfied { remaining } => remaining,
+            WaitOutcome::TimedOut => Some(Duration::ZERO),
+        }
+    }
+}
+
+fn remaining(deadline: Option<Instant>) -> Option<Duration> {
+    deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()))
+}
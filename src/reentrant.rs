@@ -0,0 +1,224 @@
+use crate::{
+    waiter::{WaitQueue, Waiter, Wakeup},
+    WaitOutcome,
+};
+use parking_lot::{ReentrantMutex, ReentrantMutexGuard};
+use std::{
+    cell::UnsafeCell,
+    fmt,
+    marker::PhantomData,
+    mem,
+    ops::Deref,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+pub struct ReentrantMonitor<T> {
+    mutex: ReentrantMutex<T>,
+    state: UnsafeCell<State>,
+}
+
+// Unlike a plain reentrant mutex, `T: Sync` is needed too: a thread waiting on
+// an inner guard lets other threads in while its outer guards still hand out
+// `&T`.
+unsafe impl<T: Send + Sync> Sync for ReentrantMonitor<T> {}
+
+// Guarded by the mutex. The mutex keeps its own recursion count private, so
+// the depth is tracked alongside it for `wait` to know how many levels to give
+// up and take back.
+#[derive(Default)]
+struct State {
+    queue: WaitQueue,
+    depth: usize,
+}
+
+impl<T> ReentrantMonitor<T> {
+    pub fn new(t: T) -> Self {
+        ReentrantMonitor {
+            mutex: ReentrantMutex::new(t),
+            state: UnsafeCell::new(State::default()),
+        }
+    }
+
+    pub fn lock(&self) -> ReentrantMonitorGuard<'_, T> {
+        mem::forget(self.mutex.lock());
+        ReentrantMonitorGuard::new(self)
+    }
+
+    pub fn try_lock(&self) -> Option<ReentrantMonitorGuard<'_, T>> {
+        let guard = self.mutex.try_lock()?;
+        mem::forget(guard);
+        Some(ReentrantMonitorGuard::new(self))
+    }
+
+    pub fn try_lock_for(&self, timeout: Duration) -> Option<ReentrantMonitorGuard<'_, T>> {
+        let guard = self.mutex.try_lock_for(timeout)?;
+        mem::forget(guard);
+        Some(ReentrantMonitorGuard::new(self))
+    }
+
+    pub fn try_lock_until(&self, timeout: Instant) -> Option<ReentrantMonitorGuard<'_, T>> {
+        let guard = self.mutex.try_lock_until(timeout)?;
+        mem::forget(guard);
+        Some(ReentrantMonitorGuard::new(self))
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.mutex.is_locked()
+    }
+
+    pub fn is_owned_by_current_thread(&self) -> bool {
+        self.mutex.is_owned_by_current_thread()
+    }
+
+    pub fn into_inner(self) -> T {
+        self.mutex.into_inner()
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.mutex.get_mut()
+    }
+
+    // Safety: the caller must own the monitor's lock.
+    unsafe fn with_state<U>(&self, f: impl FnOnce(&mut State) -> U) -> U {
+        f(&mut *self.state.get())
+    }
+}
+
+impl<T> From<T> for ReentrantMonitor<T> {
+    fn from(t: T) -> Self {
+        ReentrantMonitor::new(t)
+    }
+}
+
+impl<T: Default> Default for ReentrantMonitor<T> {
+    fn default() -> Self {
+        ReentrantMonitor::new(T::default())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ReentrantMonitor<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReentrantMonitor")
+            .field("mutex", &self.mutex)
+            .finish()
+    }
+}
+
+pub struct ReentrantMonitorGuard<'a, T> {
+    monitor: &'a ReentrantMonitor<T>,
+    _guard: PhantomData<ReentrantMutexGuard<'a, T>>,
+}
+
+impl<'a, T> ReentrantMonitorGuard<'a, T> {
+    fn new(monitor: &'a ReentrantMonitor<T>) -> Self {
+        unsafe { monitor.with_state(|s| s.depth += 1) };
+        ReentrantMonitorGuard {
+            monitor,
+            _guard: PhantomData,
+        }
+    }
+
+    pub fn notify(&mut self) -> usize {
+        unsafe { self.monitor.with_state(|s| s.queue.notify(1)) }
+    }
+
+    pub fn notify_all(&mut self) -> usize {
+        unsafe { self.monitor.with_state(|s| s.queue.notify(usize::MAX)) }
+    }
+
+    pub fn wait(&mut self) -> WaitOutcome {
+        WaitOutcome::new(self.park(None), None)
+    }
+
+    pub fn wait_for(&mut self, timeout: Duration) -> WaitOutcome {
+        let deadline = Instant::now().checked_add(timeout);
+        WaitOutcome::new(self.park(deadline), deadline)
+    }
+
+    pub fn wait_until(&mut self, timeout: Instant) -> WaitOutcome {
+        WaitOutcome::new(self.park(Some(timeout)), Some(timeout))
+    }
+
+    pub fn wait_while<F>(&mut self, condition: F) -> WaitOutcome
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.park_while(None, condition)
+    }
+
+    pub fn wait_while_for<F>(&mut self, timeout: Duration, condition: F) -> WaitOutcome
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.park_while(Instant::now().checked_add(timeout), condition)
+    }
+
+    pub fn wait_while_until<F>(&mut self, timeout: Instant, condition: F) -> WaitOutcome
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.park_while(Some(timeout), condition)
+    }
+
+    // Like `Object.wait`, every recursion level held by this thread is given
+    // up while parked, whichever guard the wait was called on, and all of them
+    // are taken back before returning.
+    fn park(&mut self, deadline: Option<Instant>) -> Wakeup {
+        let monitor = self.monitor;
+        let waiter = Waiter::new();
+        let depth = unsafe {
+            monitor.with_state(|s| {
+                s.queue.push(Arc::clone(&waiter), 0);
+                mem::replace(&mut s.depth, 0)
+            })
+        };
+        for _ in 0..depth {
+            unsafe { monitor.mutex.force_unlock() };
+        }
+
+        let wakeup = waiter.park(deadline);
+
+        for _ in 0..depth {
+            mem::forget(monitor.mutex.lock());
+        }
+        unsafe {
+            monitor.with_state(|s| {
+                s.depth = depth;
+                if wakeup == Wakeup::TimedOut {
+                    s.queue.remove(&waiter);
+                }
+            })
+        };
+        wakeup
+    }
+
+    fn park_while<F>(&mut self, deadline: Option<Instant>, mut condition: F) -> WaitOutcome
+    where
+        F: FnMut(&T) -> bool,
+    {
+        while condition(self) {
+            if self.park(deadline) == Wakeup::TimedOut && condition(self) {
+                return WaitOutcome::TimedOut;
+            }
+        }
+        WaitOutcome::satisfied(deadline)
+    }
+}
+
+impl<T> Drop for ReentrantMonitorGuard<'_, T> {
+    fn drop(&mut self) {
+        unsafe {
+            self.monitor.with_state(|s| s.depth -= 1);
+            self.monitor.mutex.force_unlock();
+        }
+    }
+}
+
+impl<T> Deref for ReentrantMonitorGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.monitor.mutex.data_ptr() }
+    }
+}
@@ -0,0 +1,454 @@
+use crate::{
+    waiter::{WaitQueue, Waiter, Wakeup},
+    WaitOutcome,
+};
+use parking_lot::{
+    lock_api::{
+        RawRwLock as _, RawRwLockDowngrade as _, RawRwLockTimed as _, RawRwLockUpgrade as _,
+        RawRwLockUpgradeDowngrade as _, RawRwLockUpgradeTimed as _,
+    },
+    Mutex, RawRwLock, RwLock, RwLockReadGuard, RwLockUpgradableReadGuard, RwLockWriteGuard,
+};
+use std::{
+    fmt,
+    marker::PhantomData,
+    mem,
+    ops::{Deref, DerefMut},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+// Readers register while holding only a shared lock, so the queue gets a lock
+// of its own. Notifying takes an exclusive lock, which still keeps a waiter
+// from missing a notification between checking its predicate and parking.
+pub struct RwMonitor<T> {
+    lock: RwLock<T>,
+    queue: Mutex<WaitQueue>,
+}
+
+#[derive(Clone, Copy)]
+enum Access {
+    Shared,
+    Exclusive,
+}
+
+impl<T> RwMonitor<T> {
+    pub fn new(t: T) -> Self {
+        RwMonitor {
+            lock: RwLock::new(t),
+            queue: Mutex::new(WaitQueue::default()),
+        }
+    }
+
+    pub fn read(&self) -> RwMonitorReadGuard<'_, T> {
+        self.raw().lock_shared();
+        RwMonitorReadGuard::new(self)
+    }
+
+    pub fn try_read(&self) -> Option<RwMonitorReadGuard<'_, T>> {
+        self.raw()
+            .try_lock_shared()
+            .then(|| RwMonitorReadGuard::new(self))
+    }
+
+    pub fn try_read_for(&self, timeout: Duration) -> Option<RwMonitorReadGuard<'_, T>> {
+        self.raw()
+            .try_lock_shared_for(timeout)
+            .then(|| RwMonitorReadGuard::new(self))
+    }
+
+    pub fn try_read_until(&self, timeout: Instant) -> Option<RwMonitorReadGuard<'_, T>> {
+        self.raw()
+            .try_lock_shared_until(timeout)
+            .then(|| RwMonitorReadGuard::new(self))
+    }
+
+    pub fn write(&self) -> RwMonitorWriteGuard<'_, T> {
+        self.raw().lock_exclusive();
+        RwMonitorWriteGuard::new(self)
+    }
+
+    pub fn try_write(&self) -> Option<RwMonitorWriteGuard<'_, T>> {
+        self.raw()
+            .try_lock_exclusive()
+            .then(|| RwMonitorWriteGuard::new(self))
+    }
+
+    pub fn try_write_for(&self, timeout: Duration) -> Option<RwMonitorWriteGuard<'_, T>> {
+        self.raw()
+            .try_lock_exclusive_for(timeout)
+            .then(|| RwMonitorWriteGuard::new(self))
+    }
+
+    pub fn try_write_until(&self, timeout: Instant) -> Option<RwMonitorWriteGuard<'_, T>> {
+        self.raw()
+            .try_lock_exclusive_until(timeout)
+            .then(|| RwMonitorWriteGuard::new(self))
+    }
+
+    pub fn upgradable_read(&self) -> RwMonitorUpgradableGuard<'_, T> {
+        self.raw().lock_upgradable();
+        RwMonitorUpgradableGuard::new(self)
+    }
+
+    pub fn try_upgradable_read(&self) -> Option<RwMonitorUpgradableGuard<'_, T>> {
+        self.raw()
+            .try_lock_upgradable()
+            .then(|| RwMonitorUpgradableGuard::new(self))
+    }
+
+    pub fn try_upgradable_read_for(
+        &self,
+        timeout: Duration,
+    ) -> Option<RwMonitorUpgradableGuard<'_, T>> {
+        self.raw()
+            .try_lock_upgradable_for(timeout)
+            .then(|| RwMonitorUpgradableGuard::new(self))
+    }
+
+    pub fn try_upgradable_read_until(
+        &self,
+        timeout: Instant,
+    ) -> Option<RwMonitorUpgradableGuard<'_, T>> {
+        self.raw()
+            .try_lock_upgradable_until(timeout)
+            .then(|| RwMonitorUpgradableGuard::new(self))
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.lock.is_locked()
+    }
+
+    pub fn is_locked_exclusive(&self) -> bool {
+        self.lock.is_locked_exclusive()
+    }
+
+    pub fn into_inner(self) -> T {
+        self.lock.into_inner()
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.lock.get_mut()
+    }
+
+    fn raw(&self) -> &RawRwLock {
+        unsafe { self.lock.raw() }
+    }
+
+    fn notify(&self, n: usize) -> usize {
+        self.queue.lock().notify(n)
+    }
+
+    // Safety: the caller must hold the lock with the given access. It is
+    // released while parked and reacquired with the same access before
+    // returning.
+    unsafe fn park(&self, access: Access, deadline: Option<Instant>) -> Wakeup {
+        let waiter = Waiter::new();
+        self.queue.lock().push(Arc::clone(&waiter), 0);
+        match access {
+            Access::Shared => self.raw().unlock_shared(),
+            Access::Exclusive => self.raw().unlock_exclusive(),
+        }
+
+        let wakeup = waiter.park(deadline);
+        if wakeup == Wakeup::TimedOut {
+            self.queue.lock().remove(&waiter);
+        }
+
+        match access {
+            Access::Shared => self.raw().lock_shared(),
+            Access::Exclusive => self.raw().lock_exclusive(),
+        }
+        wakeup
+    }
+
+    // Safety: as for `park`.
+    unsafe fn park_while(
+        &self,
+        access: Access,
+        deadline: Option<Instant>,
+        mut condition: impl FnMut() -> bool,
+    ) -> WaitOutcome {
+        while condition() {
+            if self.park(access, deadline) == Wakeup::TimedOut && condition() {
+                return WaitOutcome::TimedOut;
+            }
+        }
+        WaitOutcome::satisfied(deadline)
+    }
+}
+
+impl<T> From<T> for RwMonitor<T> {
+    fn from(t: T) -> Self {
+        RwMonitor::new(t)
+    }
+}
+
+impl<T: Default> Default for RwMonitor<T> {
+    fn default() -> Self {
+        RwMonitor::new(T::default())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for RwMonitor<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RwMonitor")
+            .field("lock", &self.lock)
+            .finish()
+    }
+}
+
+pub struct RwMonitorReadGuard<'a, T> {
+    monitor: &'a RwMonitor<T>,
+    _guard: PhantomData<RwLockReadGuard<'a, T>>,
+}
+
+impl<'a, T> RwMonitorReadGuard<'a, T> {
+    fn new(monitor: &'a RwMonitor<T>) -> Self {
+        RwMonitorReadGuard {
+            monitor,
+            _guard: PhantomData,
+        }
+    }
+
+    pub fn wait(&mut self) -> WaitOutcome {
+        self.wait_until_opt(None)
+    }
+
+    pub fn wait_for(&mut self, timeout: Duration) -> WaitOutcome {
+        self.wait_until_opt(Instant::now().checked_add(timeout))
+    }
+
+    pub fn wait_until(&mut self, timeout: Instant) -> WaitOutcome {
+        self.wait_until_opt(Some(timeout))
+    }
+
+    pub fn wait_while<F>(&mut self, condition: F) -> WaitOutcome
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.park_while(None, condition)
+    }
+
+    pub fn wait_while_for<F>(&mut self, timeout: Duration, condition: F) -> WaitOutcome
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.park_while(Instant::now().checked_add(timeout), condition)
+    }
+
+    pub fn wait_while_until<F>(&mut self, timeout: Instant, condition: F) -> WaitOutcome
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.park_while(Some(timeout), condition)
+    }
+
+    fn wait_until_opt(&mut self, deadline: Option<Instant>) -> WaitOutcome {
+        let wakeup = unsafe { self.monitor.park(Access::Shared, deadline) };
+        WaitOutcome::new(wakeup, deadline)
+    }
+
+    fn park_while<F>(&mut self, deadline: Option<Instant>, mut condition: F) -> WaitOutcome
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let monitor = self.monitor;
+        unsafe {
+            monitor.park_while(Access::Shared, deadline, || {
+                condition(&*monitor.lock.data_ptr())
+            })
+        }
+    }
+}
+
+impl<T> Drop for RwMonitorReadGuard<'_, T> {
+    fn drop(&mut self) {
+        unsafe { self.monitor.raw().unlock_shared() };
+    }
+}
+
+impl<T> Deref for RwMonitorReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.monitor.lock.data_ptr() }
+    }
+}
+
+pub struct RwMonitorWriteGuard<'a, T> {
+    monitor: &'a RwMonitor<T>,
+    _guard: PhantomData<RwLockWriteGuard<'a, T>>,
+}
+
+impl<'a, T> RwMonitorWriteGuard<'a, T> {
+    fn new(monitor: &'a RwMonitor<T>) -> Self {
+        RwMonitorWriteGuard {
+            monitor,
+            _guard: PhantomData,
+        }
+    }
+
+    pub fn notify_one(&self) -> usize {
+        self.monitor.notify(1)
+    }
+
+    pub fn notify_n(&self, n: usize) -> usize {
+        self.monitor.notify(n)
+    }
+
+    pub fn notify_all(&self) -> usize {
+        self.monitor.notify(usize::MAX)
+    }
+
+    pub fn wait(&mut self) -> WaitOutcome {
+        self.wait_until_opt(None)
+    }
+
+    pub fn wait_for(&mut self, timeout: Duration) -> WaitOutcome {
+        self.wait_until_opt(Instant::now().checked_add(timeout))
+    }
+
+    pub fn wait_until(&mut self, timeout: Instant) -> WaitOutcome {
+        self.wait_until_opt(Some(timeout))
+    }
+
+    pub fn wait_while<F>(&mut self, condition: F) -> WaitOutcome
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.park_while(None, condition)
+    }
+
+    pub fn wait_while_for<F>(&mut self, timeout: Duration, condition: F) -> WaitOutcome
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.park_while(Instant::now().checked_add(timeout), condition)
+    }
+
+    pub fn wait_while_until<F>(&mut self, timeout: Instant, condition: F) -> WaitOutcome
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.park_while(Some(timeout), condition)
+    }
+
+    pub fn downgrade(self) -> RwMonitorReadGuard<'a, T> {
+        let monitor = self.monitor;
+        mem::forget(self);
+        unsafe { monitor.raw().downgrade() };
+        RwMonitorReadGuard::new(monitor)
+    }
+
+    pub fn downgrade_to_upgradable(self) -> RwMonitorUpgradableGuard<'a, T> {
+        let monitor = self.monitor;
+        mem::forget(self);
+        unsafe { monitor.raw().downgrade_to_upgradable() };
+        RwMonitorUpgradableGuard::new(monitor)
+    }
+
+    fn wait_until_opt(&mut self, deadline: Option<Instant>) -> WaitOutcome {
+        let wakeup = unsafe { self.monitor.park(Access::Exclusive, deadline) };
+        WaitOutcome::new(wakeup, deadline)
+    }
+
+    fn park_while<F>(&mut self, deadline: Option<Instant>, mut condition: F) -> WaitOutcome
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        let monitor = self.monitor;
+        unsafe {
+            monitor.park_while(Access::Exclusive, deadline, || {
+                condition(&mut *monitor.lock.data_ptr())
+            })
+        }
+    }
+}
+
+impl<T> Drop for RwMonitorWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        unsafe { self.monitor.raw().unlock_exclusive() };
+    }
+}
+
+impl<T> Deref for RwMonitorWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.monitor.lock.data_ptr() }
+    }
+}
+
+impl<T> DerefMut for RwMonitorWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.monitor.lock.data_ptr() }
+    }
+}
+
+pub struct RwMonitorUpgradableGuard<'a, T> {
+    monitor: &'a RwMonitor<T>,
+    _guard: PhantomData<RwLockUpgradableReadGuard<'a, T>>,
+}
+
+impl<'a, T> RwMonitorUpgradableGuard<'a, T> {
+    fn new(monitor: &'a RwMonitor<T>) -> Self {
+        RwMonitorUpgradableGuard {
+            monitor,
+            _guard: PhantomData,
+        }
+    }
+
+    pub fn upgrade(self) -> RwMonitorWriteGuard<'a, T> {
+        let monitor = self.monitor;
+        mem::forget(self);
+        unsafe { monitor.raw().upgrade() };
+        RwMonitorWriteGuard::new(monitor)
+    }
+
+    pub fn try_upgrade(self) -> Result<RwMonitorWriteGuard<'a, T>, Self> {
+        self.try_upgrade_with(|raw| unsafe { raw.try_upgrade() })
+    }
+
+    pub fn try_upgrade_for(self, timeout: Duration) -> Result<RwMonitorWriteGuard<'a, T>, Self> {
+        self.try_upgrade_with(|raw| unsafe { raw.try_upgrade_for(timeout) })
+    }
+
+    pub fn try_upgrade_until(self, timeout: Instant) -> Result<RwMonitorWriteGuard<'a, T>, Self> {
+        self.try_upgrade_with(|raw| unsafe { raw.try_upgrade_until(timeout) })
+    }
+
+    pub fn downgrade(self) -> RwMonitorReadGuard<'a, T> {
+        let monitor = self.monitor;
+        mem::forget(self);
+        unsafe { monitor.raw().downgrade_upgradable() };
+        RwMonitorReadGuard::new(monitor)
+    }
+
+    fn try_upgrade_with(
+        self,
+        upgrade: impl FnOnce(&RawRwLock) -> bool,
+    ) -> Result<RwMonitorWriteGuard<'a, T>, Self> {
+        let monitor = self.monitor;
+        if upgrade(monitor.raw()) {
+            mem::forget(self);
+            Ok(RwMonitorWriteGuard::new(monitor))
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl<T> Drop for RwMonitorUpgradableGuard<'_, T> {
+    fn drop(&mut self) {
+        unsafe { self.monitor.raw().unlock_upgradable() };
+    }
+}
+
+impl<T> Deref for RwMonitorUpgradableGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.monitor.lock.data_ptr() }
+    }
+}
@@ -0,0 +1,304 @@
+use crate::{
+    waiter::{Waiter, Wakeup},
+    ConditionKey, Instant, Monitor, MonitorGuard,
+};
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+use core::{fmt, sync::atomic::Ordering, time::Duration};
+use lock_api::RawMutex;
+
+#[derive(Default)]
+pub struct Select<'a> {
+    cases: Vec<Box<dyn Case + 'a>>,
+}
+
+// A case whose predicate holds is ready even if its monitor has been closed,
+// as for the predicate waits on a guard.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Readiness {
+    Pending,
+    Ready,
+    Closed,
+}
+
+trait Case {
+    fn monitor(&self) -> *const ();
+
+    // Leaves the monitor locked if and only if the case is ready.
+    fn lock_if_ready(&mut self) -> Readiness;
+
+    // Only queues the waiter if the case is pending.
+    fn register(&mut self, waiter: &Arc<Waiter>) -> Readiness;
+
+    fn unregister(&self, waiter: &Arc<Waiter>);
+
+    // Safety: the caller must own the monitor's lock.
+    unsafe fn unlock(&self);
+}
+
+struct MonitorCase<'a, T, C, R, F> {
+    monitor: &'a Monitor<T, C, R>,
+    queue: usize,
+    condition: F,
+}
+
+impl<T, C, R, F> MonitorCase<'_, T, C, R, F>
+where
+    R: RawMutex,
+    F: FnMut(&mut T) -> bool,
+{
+    // Safety: the caller must own the monitor's lock.
+    unsafe fn readiness(&mut self) -> Readiness {
+        if (self.condition)(&mut *self.monitor.mutex.data_ptr()) {
+            Readiness::Ready
+        } else if self.monitor.closed.load(Ordering::Acquire) {
+            Readiness::Closed
+        } else {
+            Readiness::Pending
+        }
+    }
+}
+
+impl<T, C, R, F> Case for MonitorCase<'_, T, C, R, F>
+where
+    R: RawMutex,
+    F: FnMut(&mut T) -> bool,
+{
+    fn monitor(&self) -> *const () {
+        self.monitor as *const Monitor<T, C, R> as *const ()
+    }
+
+    fn lock_if_ready(&mut self) -> Readiness {
+        self.monitor.acquire();
+        let readiness = unsafe { self.readiness() };
+        if readiness != Readiness::Ready {
+            unsafe { self.monitor.release() };
+        }
+        readiness
+    }
+
+    fn register(&mut self, waiter: &Arc<Waiter>) -> Readiness {
+        let monitor = self.monitor;
+        monitor.acquire();
+        let readiness = unsafe { self.readiness() };
+        if readiness == Readiness::Pending {
+            monitor.waiting[self.queue].fetch_add(1, Ordering::Relaxed);
+            unsafe { monitor.with_state(|s| s.queues[self.queue].push(Arc::clone(waiter), 0)) };
+        }
+        unsafe { monitor.release() };
+        readiness
+    }
+
+    fn unregister(&self, waiter: &Arc<Waiter>) {
+        let monitor = self.monitor;
+        monitor.acquire();
+        unsafe {
+            monitor.with_state(|s| s.queues[self.queue].remove(waiter));
+            monitor.release();
+        }
+        monitor.waiting[self.queue].fetch_sub(1, Ordering::Relaxed);
+    }
+
+    unsafe fn unlock(&self) {
+        self.monitor.release();
+    }
+}
+
+impl<'a> Select<'a> {
+    pub fn new() -> Self {
+        Select::default()
+    }
+
+    pub fn wait<T, C, R, F>(&mut self, monitor: &'a Monitor<T, C, R>, condition: F) -> usize
+    where
+        T: 'a,
+        C: 'a,
+        R: RawMutex + 'a,
+        F: FnMut(&mut T) -> bool + 'a,
+    {
+        self.push(monitor, 0, condition)
+    }
+
+    pub fn wait_on<T, C, R, F>(
+        &mut self,
+        monitor: &'a Monitor<T, C, R>,
+        cond: C,
+        condition: F,
+    ) -> usize
+    where
+        T: 'a,
+        C: ConditionKey + 'a,
+        R: RawMutex + 'a,
+        F: FnMut(&mut T) -> bool + 'a,
+    {
+        self.push(monitor, monitor.condition(cond), condition)
+    }
+
+    // A case whose monitor is closed while its predicate does not hold ends
+    // the select with `SelectClosed`, since nothing may ever make it ready.
+    // No lock is held then.
+    pub fn ready(&mut self) -> Result<Selected<'_, 'a>, SelectClosed> {
+        let index = self
+            .select(None)?
+            .expect("select without a deadline timed out");
+        Ok(Selected::new(self, index))
+    }
+
+    pub fn ready_for(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<Option<Selected<'_, 'a>>, SelectClosed> {
+        let index = self.select(Instant::now().checked_add(timeout))?;
+        Ok(index.map(|index| Selected::new(self, index)))
+    }
+
+    pub fn ready_until(
+        &mut self,
+        timeout: Instant,
+    ) -> Result<Option<Selected<'_, 'a>>, SelectClosed> {
+        let index = self.select(Some(timeout))?;
+        Ok(index.map(|index| Selected::new(self, index)))
+    }
+
+    fn push<T, C, R, F>(
+        &mut self,
+        monitor: &'a Monitor<T, C, R>,
+        queue: usize,
+        condition: F,
+    ) -> usize
+    where
+        T: 'a,
+        C: 'a,
+        R: RawMutex + 'a,
+        F: FnMut(&mut T) -> bool + 'a,
+    {
+        self.cases.push(Box::new(MonitorCase {
+            monitor,
+            queue,
+            condition,
+        }));
+        self.cases.len() - 1
+    }
+
+    fn poll(&mut self) -> Result<Option<usize>, SelectClosed> {
+        for (index, case) in self.cases.iter_mut().enumerate() {
+            match case.lock_if_ready() {
+                Readiness::Pending => {}
+                Readiness::Ready => return Ok(Some(index)),
+                Readiness::Closed => return Err(SelectClosed { index }),
+            }
+        }
+        Ok(None)
+    }
+
+    // The waiter is queued on every monitor in turn, one lock at a time, so a
+    // notification on any of them wakes us. Cases are always re-checked with
+    // nothing queued before returning, so no monitor is left holding a stale
+    // registration.
+    fn select(&mut self, deadline: Option<Instant>) -> Result<Option<usize>, SelectClosed> {
+        assert!(!self.cases.is_empty(), "select with no cases");
+
+        loop {
+            if let Some(index) = self.poll()? {
+                return Ok(Some(index));
+            }
+
+            let waiter = Waiter::notify_only();
+            let mut registered = 0;
+            let mut ready = false;
+            for case in &mut self.cases {
+                if case.register(&waiter) != Readiness::Pending {
+                    ready = true;
+                    break;
+                }
+                registered += 1;
+            }
+
+            let timed_out = !ready && waiter.park(deadline) == Wakeup::TimedOut;
+            for case in &self.cases[..registered] {
+                case.unregister(&waiter);
+            }
+
+            if timed_out {
+                return self.poll();
+            }
+        }
+    }
+}
+
+// The index of the case whose monitor was found closed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SelectClosed {
+    index: usize,
+}
+
+impl SelectClosed {
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl fmt::Display for SelectClosed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the monitor of select case {} was closed", self.index)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SelectClosed {}
+
+impl fmt::Debug for Select<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Select")
+            .field("cases", &self.cases.len())
+            .finish()
+    }
+}
+
+pub struct Selected<'s, 'a> {
+    select: &'s Select<'a>,
+    index: usize,
+    taken: bool,
+}
+
+impl<'s, 'a> Selected<'s, 'a> {
+    fn new(select: &'s Select<'a>, index: usize) -> Self {
+        Selected {
+            select,
+            index,
+            taken: false,
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn guard<T, C: ConditionKey, R: RawMutex>(
+        mut self,
+        monitor: &'a Monitor<T, C, R>,
+    ) -> MonitorGuard<'a, T, C, R> {
+        let selected = self.select.cases[self.index].monitor();
+        assert!(
+            selected == monitor as *const Monitor<T, C, R> as *const (),
+            "monitor does not belong to the selected case"
+        );
+        self.taken = true;
+        MonitorGuard::new(monitor)
+    }
+}
+
+impl Drop for Selected<'_, '_> {
+    fn drop(&mut self) {
+        if !self.taken {
+            unsafe { self.select.cases[self.index].unlock() };
+        }
+    }
+}
+
+impl fmt::Debug for Selected<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Selected")
+            .field("index", &self.index)
+            .finish()
+    }
+}
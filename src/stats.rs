@@ -0,0 +1,228 @@
+// Contention and wait statistics for the `stats` feature. Without the feature
+// every hook is a no-op and `Stats` takes up no space in a `Monitor`.
+
+#[cfg(feature = "stats")]
+pub(crate) use enabled::*;
+
+#[cfg(feature = "stats")]
+pub use enabled::{Histogram, MonitorStats};
+
+#[cfg(not(feature = "stats"))]
+pub(crate) use disabled::*;
+
+#[cfg(feature = "stats")]
+mod enabled {
+    use crate::{Instant, Mutex};
+    use core::time::Duration;
+
+    // Every hook runs while the monitor's lock is held, so the mutex in here
+    // is only ever contended by readers of the statistics. Timing every
+    // acquisition needs a clock, hence the feature's dependency on `std`.
+    #[derive(Default)]
+    pub(crate) struct Stats(Mutex<Record>);
+
+    #[derive(Default)]
+    struct Record {
+        stats: MonitorStats,
+        held_since: Option<Instant>,
+    }
+
+    // When a thread started blocking on the lock.
+    pub(crate) struct Stamp(Instant);
+
+    impl Stamp {
+        pub(crate) fn now() -> Self {
+            Stamp(Instant::now())
+        }
+    }
+
+    impl Stats {
+        pub(crate) fn new() -> Self {
+            Stats::default()
+        }
+
+        pub(crate) fn acquired(&self, blocked: Option<Stamp>) {
+            let now = Instant::now();
+            let mut record = self.0.lock();
+            record.stats.acquisitions += 1;
+            if let Some(Stamp(since)) = blocked {
+                record.stats.contended += 1;
+                record
+                    .stats
+                    .blocked
+                    .record(now.saturating_duration_since(since));
+            }
+            record.held_since = Some(now);
+        }
+
+        pub(crate) fn released(&self) {
+            let now = Instant::now();
+            let mut record = self.0.lock();
+            if let Some(since) = record.held_since.take() {
+                record
+                    .stats
+                    .held
+                    .record(now.saturating_duration_since(since));
+            }
+        }
+
+        pub(crate) fn waited(&self) {
+            self.0.lock().stats.waits += 1;
+        }
+
+        pub(crate) fn spurious(&self) {
+            self.0.lock().stats.spurious += 1;
+        }
+
+        pub(crate) fn notified(&self, n: usize) {
+            self.0.lock().stats.notifications += n as u64;
+        }
+
+        pub(crate) fn snapshot(&self) -> MonitorStats {
+            self.0.lock().stats.clone()
+        }
+
+        // The current holder, if any, still gets its hold time recorded.
+        pub(crate) fn reset(&self) {
+            self.0.lock().stats = MonitorStats::default();
+        }
+    }
+
+    #[derive(Debug, Default, Clone, PartialEq, Eq)]
+    pub struct MonitorStats {
+        acquisitions: u64,
+        contended: u64,
+        blocked: Histogram,
+        held: Histogram,
+        waits: u64,
+        spurious: u64,
+        notifications: u64,
+    }
+
+    impl MonitorStats {
+        pub fn acquisitions(&self) -> u64 {
+            self.acquisitions
+        }
+
+        pub fn contended_acquisitions(&self) -> u64 {
+            self.contended
+        }
+
+        // Time spent blocked on the lock, for contended acquisitions only.
+        pub fn blocked(&self) -> &Histogram {
+            &self.blocked
+        }
+
+        // Time from each acquisition of the lock to its release, so a guard
+        // that waits contributes one entry per stretch it held the lock.
+        pub fn held(&self) -> &Histogram {
+            &self.held
+        }
+
+        pub fn waits(&self) -> u64 {
+            self.waits
+        }
+
+        // Wakeups that left a `wait_while*` predicate still holding.
+        pub fn spurious_wakeups(&self) -> u64 {
+            self.spurious
+        }
+
+        // Waiters woken by a notify, not calls made to one.
+        pub fn notifications(&self) -> u64 {
+            self.notifications
+        }
+    }
+
+    const BUCKETS: usize = 65;
+
+    // Bucket 0 counts zero durations and bucket `i` counts those of at least
+    // 2^(i - 1) but under 2^i nanoseconds.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct Histogram {
+        buckets: [u64; BUCKETS],
+        count: u64,
+        total: Duration,
+        max: Duration,
+    }
+
+    impl Histogram {
+        fn record(&mut self, duration: Duration) {
+            let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+            self.buckets[(u64::BITS - nanos.leading_zeros()) as usize] += 1;
+            self.count += 1;
+            self.total = self.total.saturating_add(duration);
+            self.max = self.max.max(duration);
+        }
+
+        pub fn count(&self) -> u64 {
+            self.count
+        }
+
+        pub fn total(&self) -> Duration {
+            self.total
+        }
+
+        pub fn max(&self) -> Duration {
+            self.max
+        }
+
+        pub fn mean(&self) -> Duration {
+            match self.count {
+                0 => Duration::ZERO,
+                count => Duration::from_nanos((self.total.as_nanos() / count as u128) as u64),
+            }
+        }
+
+        pub fn buckets(&self) -> &[u64] {
+            &self.buckets
+        }
+    }
+
+    impl Default for Histogram {
+        fn default() -> Self {
+            Histogram {
+                buckets: [0; BUCKETS],
+                count: 0,
+                total: Duration::ZERO,
+                max: Duration::ZERO,
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "stats"))]
+mod disabled {
+    pub(crate) struct Stats;
+
+    pub(crate) struct Stamp;
+
+    impl Stamp {
+        #[inline]
+        pub(crate) fn now() -> Self {
+            Stamp
+        }
+    }
+
+    impl Stats {
+        #[inline]
+        pub(crate) fn new() -> Self {
+            Stats
+        }
+
+        #[inline]
+        pub(crate) fn acquired(&self, _blocked: Option<Stamp>) {}
+
+        #[inline]
+        pub(crate) fn released(&self) {}
+
+        #[inline]
+        pub(crate) fn waited(&self) {}
+
+        #[inline]
+        pub(crate) fn spurious(&self) {}
+
+        #[inline]
+        pub(crate) fn notified(&self, _n: usize) {}
+    }
+}
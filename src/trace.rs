@@ -0,0 +1,117 @@
+// Spans and events for the `tracing` feature. Locking and waiting each get a
+// span naming the monitor and the caller's location, closed by an event with
+// how long the call took; notifying is a single event. Without the feature
+// every hook is a no-op.
+
+#[cfg(feature = "tracing")]
+pub(crate) use enabled::*;
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) use disabled::*;
+
+#[cfg(feature = "tracing")]
+mod enabled {
+    use crate::{Instant, WaitOutcome};
+    use core::panic::Location;
+    use tracing::{debug, debug_span, span::EnteredSpan, trace, trace_span};
+
+    pub(crate) struct Lock {
+        _span: EnteredSpan,
+        start: Instant,
+    }
+
+    pub(crate) struct Wait {
+        _span: EnteredSpan,
+        start: Instant,
+    }
+
+    #[track_caller]
+    pub(crate) fn locking(name: Option<&'static str>, key: usize) -> Lock {
+        let span = trace_span!(
+            "monitor.lock",
+            monitor = name,
+            addr = ?(key as *const ()),
+            location = %Location::caller(),
+        );
+        Lock {
+            _span: span.entered(),
+            start: Instant::now(),
+        }
+    }
+
+    impl Lock {
+        pub(crate) fn locked(self, locked: bool) {
+            trace!(locked, elapsed = ?self.start.elapsed(), "lock attempt finished");
+        }
+    }
+
+    #[track_caller]
+    pub(crate) fn waiting(name: Option<&'static str>, key: usize, queue: usize) -> Wait {
+        let span = debug_span!(
+            "monitor.wait",
+            monitor = name,
+            addr = ?(key as *const ()),
+            location = %Location::caller(),
+            condition = condition(queue),
+        );
+        Wait {
+            _span: span.entered(),
+            start: Instant::now(),
+        }
+    }
+
+    impl Wait {
+        pub(crate) fn woke(self, outcome: &WaitOutcome) {
+            debug!(outcome = ?outcome, elapsed = ?self.start.elapsed(), "wait finished");
+        }
+    }
+
+    #[track_caller]
+    pub(crate) fn notified(name: Option<&'static str>, key: usize, queue: usize, n: usize) {
+        debug!(
+            monitor = name,
+            addr = ?(key as *const ()),
+            location = %Location::caller(),
+            condition = condition(queue),
+            notified = n,
+            "monitor notified",
+        );
+    }
+
+    // Queue 0 is the unkeyed one, so it has no condition to report.
+    fn condition(queue: usize) -> Option<u64> {
+        queue.checked_sub(1).map(|c| c as u64)
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+mod disabled {
+    use crate::WaitOutcome;
+
+    pub(crate) struct Lock;
+
+    pub(crate) struct Wait;
+
+    #[inline]
+    pub(crate) fn locking(_name: Option<&'static str>, _key: usize) -> Lock {
+        Lock
+    }
+
+    impl Lock {
+        #[inline]
+        pub(crate) fn locked(self, _locked: bool) {}
+    }
+
+    #[inline]
+    pub(crate) fn waiting(_name: Option<&'static str>, _key: usize, _queue: usize) -> Wait {
+        Wait
+    }
+
+    impl Wait {
+        #[inline]
+        pub(crate) fn woke(self, _outcome: &WaitOutcome) {}
+    }
+
+    #[inline]
+    pub(crate) fn notified(_name: Option<&'static str>, _key: usize, _queue: usize, _n: usize) {}
+}
@@ -0,0 +1,235 @@
+use crate::{backend, Instant, Mutex};
+use alloc::{collections::VecDeque, sync::Arc};
+use core::{
+    sync::atomic::{AtomicU8, Ordering},
+    task::{Context, Poll, Waker},
+};
+
+const WAITING: u8 = 0;
+const SELECTED: u8 = 1;
+const NOTIFIED: u8 = 2;
+const HANDOFF: u8 = 3;
+const TIMED_OUT: u8 = 4;
+const CANCELLED: u8 = 5;
+const CLOSED: u8 = 6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Wakeup {
+    Notified,
+    Handoff,
+    TimedOut,
+    Cancelled,
+    Closed,
+}
+
+pub(crate) struct Waiter {
+    state: AtomicU8,
+    unparker: Unparker,
+    handoff: bool,
+}
+
+// Threads park on the waiter's own address through the backend.
+enum Unparker {
+    Thread,
+    Task(Mutex<Option<Waker>>),
+}
+
+impl Waiter {
+    pub(crate) fn new() -> Arc<Self> {
+        Arc::new(Waiter {
+            state: AtomicU8::new(WAITING),
+            unparker: Unparker::Thread,
+            handoff: true,
+        })
+    }
+
+    // For threads queued on several monitors at once, which could not tell
+    // whose lock they had been handed. Hoare signals wake them Mesa-style.
+    pub(crate) fn notify_only() -> Arc<Self> {
+        Arc::new(Waiter {
+            state: AtomicU8::new(WAITING),
+            unparker: Unparker::Thread,
+            handoff: false,
+        })
+    }
+
+    pub(crate) fn task() -> Arc<Self> {
+        Arc::new(Waiter {
+            state: AtomicU8::new(WAITING),
+            unparker: Unparker::Task(Mutex::new(None)),
+            handoff: true,
+        })
+    }
+
+    fn unpark(&self) {
+        match &self.unparker {
+            Unparker::Thread => backend::get().unpark(self.key()),
+            Unparker::Task(waker) => {
+                if let Some(waker) = waker.lock().take() {
+                    waker.wake();
+                }
+            }
+        }
+    }
+
+    fn key(&self) -> usize {
+        self as *const Waiter as usize
+    }
+
+    fn transition(&self, to: u8) -> bool {
+        self.state
+            .compare_exchange(WAITING, to, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+    }
+
+    fn wake(&self, to: u8) -> bool {
+        let woken = self.transition(to);
+        if woken {
+            self.unpark();
+        }
+        woken
+    }
+
+    fn notify(&self) -> bool {
+        self.wake(NOTIFIED)
+    }
+
+    pub(crate) fn cancel(&self) -> bool {
+        self.wake(CANCELLED)
+    }
+
+    fn close(&self) -> bool {
+        self.wake(CLOSED)
+    }
+
+    pub(crate) fn handoff(&self) -> bool {
+        self.wake(HANDOFF)
+    }
+
+    pub(crate) fn select(&self) -> bool {
+        self.transition(SELECTED)
+    }
+
+    pub(crate) fn accepts_handoff(&self) -> bool {
+        self.handoff
+    }
+
+    pub(crate) fn complete_notify(&self) {
+        self.state.store(NOTIFIED, Ordering::Release);
+        self.unpark();
+    }
+
+    // Lock ownership moves to the waiter once this is called, so the caller
+    // must not touch any state guarded by the monitor afterwards.
+    pub(crate) fn complete_handoff(&self) {
+        self.state.store(HANDOFF, Ordering::Release);
+        self.unpark();
+    }
+
+    fn wakeup(&self) -> Option<Wakeup> {
+        match self.state.load(Ordering::Acquire) {
+            NOTIFIED => Some(Wakeup::Notified),
+            HANDOFF => Some(Wakeup::Handoff),
+            TIMED_OUT => Some(Wakeup::TimedOut),
+            CANCELLED => Some(Wakeup::Cancelled),
+            CLOSED => Some(Wakeup::Closed),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn poll(&self, cx: &mut Context<'_>) -> Poll<Wakeup> {
+        if let Some(wakeup) = self.wakeup() {
+            return Poll::Ready(wakeup);
+        }
+        if let Unparker::Task(waker) = &self.unparker {
+            waker.lock().replace(cx.waker().clone());
+        }
+        // Re-check after publishing the waker in case we were woken in between.
+        match self.wakeup() {
+            Some(wakeup) => Poll::Ready(wakeup),
+            None => Poll::Pending,
+        }
+    }
+
+    pub(crate) fn park(&self, deadline: Option<Instant>) -> Wakeup {
+        let backend = backend::get();
+        let validate = || self.wakeup().is_none();
+        loop {
+            if let Some(wakeup) = self.wakeup() {
+                return wakeup;
+            }
+            if self.state.load(Ordering::Acquire) == SELECTED {
+                backend.park(self.key(), &validate, None);
+                continue;
+            }
+
+            if let Some(deadline) = deadline {
+                if Instant::now() >= deadline {
+                    if self.transition(TIMED_OUT) {
+                        return Wakeup::TimedOut;
+                    }
+                    continue;
+                }
+            }
+            backend.park(self.key(), &validate, deadline);
+        }
+    }
+}
+
+// Kept sorted by descending priority, FIFO among equal priorities, so the
+// front is always the next waiter to wake.
+#[derive(Default)]
+pub(crate) struct WaitQueue {
+    waiters: VecDeque<(i32, Arc<Waiter>)>,
+}
+
+impl WaitQueue {
+    pub(crate) fn push(&mut self, waiter: Arc<Waiter>, priority: i32) {
+        let index = self
+            .waiters
+            .iter()
+            .rposition(|&(p, _)| p >= priority)
+            .map_or(0, |i| i + 1);
+        self.waiters.insert(index, (priority, waiter));
+    }
+
+    pub(crate) fn remove(&mut self, waiter: &Arc<Waiter>) {
+        self.waiters.retain(|(_, w)| !Arc::ptr_eq(w, waiter));
+    }
+
+    pub(crate) fn notify(&mut self, n: usize) -> usize {
+        let mut notified = 0;
+        while notified < n {
+            match self.waiters.pop_front() {
+                Some((_, waiter)) => notified += waiter.notify() as usize,
+                None => break,
+            }
+        }
+        notified
+    }
+
+    pub(crate) fn close(&mut self) -> usize {
+        self.waiters
+            .drain(..)
+            .filter(|(_, waiter)| waiter.close())
+            .count()
+    }
+
+    pub(crate) fn handoff(&mut self) -> bool {
+        while let Some((_, waiter)) = self.waiters.pop_front() {
+            if waiter.handoff() {
+                return true;
+            }
+        }
+        false
+    }
+
+    pub(crate) fn select(&mut self) -> Option<Arc<Waiter>> {
+        while let Some((_, waiter)) = self.waiters.pop_front() {
+            if waiter.select() {
+                return Some(waiter);
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,76 @@
+#![cfg(feature = "std")]
+
+mod common;
+
+use common::await_waiters;
+use parking_monitor::{Monitor, WaitOutcome};
+use std::{sync::Arc, thread, time::Duration};
+
+// The guard keeps the monitor alive on its own, so it can outlive every other
+// handle and be moved into a `'static` closure that waits through it.
+#[test]
+fn guard_waits_from_static_closure() {
+    let monitor = Arc::new(Monitor::new(0));
+    let guard = monitor.lock_arc();
+    let task: Box<dyn FnOnce() -> (WaitOutcome, usize)> = Box::new(move || {
+        let mut guard = guard;
+        let outcome = guard.wait_while(|v| *v == 0);
+        *guard += 1;
+        assert_eq!(guard.notify_one(), 1);
+        (outcome, *guard)
+    });
+
+    let notifier = {
+        let monitor = Arc::clone(&monitor);
+        thread::spawn(move || {
+            await_waiters(&monitor, 1);
+            let mut guard = monitor.lock_arc();
+            *guard = 1;
+            assert_eq!(guard.notify_one(), 1);
+            let outcome = guard.wait_while(|v| *v == 1);
+            assert!(matches!(outcome, WaitOutcome::PredicateSatisfied { .. }));
+        })
+    };
+    drop(monitor);
+
+    let (outcome, seen) = task();
+    assert!(matches!(outcome, WaitOutcome::PredicateSatisfied { .. }));
+    assert_eq!(seen, 2);
+    notifier.join().unwrap();
+}
+
+#[test]
+fn try_lock_arc_fails_while_locked() {
+    let monitor = Arc::new(Monitor::new(()));
+    let guard = monitor.lock_arc();
+    assert!(Arc::ptr_eq(guard.monitor(), &monitor));
+    assert!(monitor.try_lock_arc().is_none());
+    assert!(monitor
+        .try_lock_arc_for(Duration::from_millis(10))
+        .is_none());
+    drop(guard);
+    assert!(monitor.try_lock_arc().is_some());
+    assert!(monitor
+        .try_lock_arc_for(Duration::from_millis(10))
+        .is_some());
+    assert_eq!(Arc::strong_count(&monitor), 1);
+}
+
+// `unlock_fair` moves the guard's handle out rather than dropping the guard,
+// which must neither leak that handle nor release it twice.
+#[test]
+fn unlock_fair_releases_the_handle_once() {
+    let monitor = Arc::new(Monitor::new(()));
+    let guard = monitor.lock_arc();
+    assert_eq!(Arc::strong_count(&monitor), 2);
+    guard.unlock_fair();
+    assert_eq!(Arc::strong_count(&monitor), 1);
+    assert!(!monitor.is_locked());
+
+    let mut guard = monitor.try_lock_arc().unwrap();
+    guard.bump_fair();
+    assert_eq!(Arc::strong_count(&monitor), 2);
+    drop(guard);
+    assert_eq!(Arc::strong_count(&monitor), 1);
+    assert!(!monitor.is_locked());
+}
@@ -0,0 +1,188 @@
+#![cfg(feature = "std")]
+
+use parking_monitor::AsyncMonitor;
+use std::{
+    fmt,
+    future::Future,
+    pin::{pin, Pin},
+    sync::Arc,
+    task::{Context, Poll, Wake, Waker},
+    thread::{self, Thread},
+};
+
+fn poll_once<F: Future>(future: Pin<&mut F>) -> Poll<F::Output> {
+    future.poll(&mut Context::from_waker(Waker::noop()))
+}
+
+struct Unpark(Thread);
+
+impl Wake for Unpark {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    let waker = Waker::from(Arc::new(Unpark(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+#[test]
+fn lock_future_dropped_after_handoff_releases_the_lock() {
+    let monitor = AsyncMonitor::new(0);
+    let guard = monitor.try_lock().unwrap();
+    {
+        let mut lock = pin!(monitor.lock());
+        assert!(poll_once(lock.as_mut()).is_pending());
+        // The release hands the lock to the pending future, which never
+        // collects it.
+        drop(guard);
+        assert!(monitor.is_locked());
+    }
+    assert!(monitor.try_lock().is_some());
+}
+
+#[test]
+fn lock_future_dropped_before_handoff_leaves_the_queue() {
+    let monitor = AsyncMonitor::new(0);
+    let guard = monitor.try_lock().unwrap();
+    {
+        let mut lock = pin!(monitor.lock());
+        assert!(poll_once(lock.as_mut()).is_pending());
+    }
+    drop(guard);
+    assert!(!monitor.is_locked());
+    assert!(monitor.try_lock().is_some());
+}
+
+#[test]
+fn wait_future_dropped_after_notify_passes_it_on() {
+    let monitor = AsyncMonitor::new(0);
+    let mut first = Box::pin(monitor.try_lock().unwrap().wait());
+    assert!(poll_once(first.as_mut()).is_pending());
+    let mut second = pin!(monitor.try_lock().unwrap().wait());
+    assert!(poll_once(second.as_mut()).is_pending());
+
+    let mut guard = monitor.try_lock().unwrap();
+    *guard = 1;
+    assert_eq!(guard.notify_one(), 1);
+    drop(guard);
+
+    // Only the first waiter was notified, but it goes away without looking.
+    assert!(poll_once(second.as_mut()).is_pending());
+    drop(first);
+    match poll_once(second.as_mut()) {
+        Poll::Ready(guard) => assert_eq!(*guard, 1),
+        Poll::Pending => panic!("notification was lost with the dropped future"),
+    };
+}
+
+#[test]
+fn wait_future_dropped_before_notify_is_not_counted() {
+    let monitor = AsyncMonitor::new(0);
+    {
+        let mut wait = pin!(monitor.try_lock().unwrap().wait());
+        assert!(poll_once(wait.as_mut()).is_pending());
+    }
+    assert_eq!(monitor.try_lock().unwrap().notify_one(), 0);
+}
+
+#[test]
+fn blocking_producer_wakes_async_consumer() {
+    let monitor = Arc::new(AsyncMonitor::new(Vec::new()));
+    let consumer = {
+        let monitor = Arc::clone(&monitor);
+        thread::spawn(move || {
+            block_on(async {
+                let mut taken = Vec::new();
+                while taken.len() < 100 {
+                    let mut guard = monitor.lock().await;
+                    guard = guard.wait_while(|items| items.is_empty()).await;
+                    taken.append(&mut guard);
+                }
+                taken
+            })
+        })
+    };
+
+    for i in 0..100 {
+        let mut guard = monitor.lock_blocking();
+        guard.push(i);
+        guard.notify_one();
+    }
+    assert_eq!(consumer.join().unwrap(), (0..100).collect::<Vec<_>>());
+}
+
+#[test]
+fn async_producer_wakes_blocking_consumer() {
+    let monitor = Arc::new(AsyncMonitor::new(Vec::new()));
+    let consumer = {
+        let monitor = Arc::clone(&monitor);
+        thread::spawn(move || {
+            let mut taken = Vec::new();
+            while taken.len() < 100 {
+                let mut guard = monitor.lock_blocking();
+                guard.wait_while_blocking(|items| items.is_empty());
+                taken.append(&mut guard);
+            }
+            taken
+        })
+    };
+
+    block_on(async {
+        for i in 0..100 {
+            let mut guard = monitor.lock().await;
+            guard.push(i);
+            guard.notify_one();
+        }
+    });
+    assert_eq!(consumer.join().unwrap(), (0..100).collect::<Vec<_>>());
+}
+
+// Formats slowly, so that lockers queue up while `Debug` holds the lock.
+struct Slow(usize);
+
+impl fmt::Debug for Slow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        thread::yield_now();
+        self.0.fmt(f)
+    }
+}
+
+// With a single locker, nobody else's release would pass on a lock that the
+// formatting failed to hand over.
+#[test]
+fn debug_hands_the_lock_to_queued_lockers() {
+    let monitor = Arc::new(AsyncMonitor::new(Slow(0)));
+    assert_eq!(
+        format!("{monitor:?}"),
+        "AsyncMonitor { data: 0, locked: false }"
+    );
+
+    let locker = {
+        let monitor = Arc::clone(&monitor);
+        thread::spawn(move || {
+            for _ in 0..10_000 {
+                monitor.lock_blocking().0 += 1;
+            }
+        })
+    };
+    while !locker.is_finished() {
+        let _ = format!("{monitor:?}");
+    }
+    locker.join().unwrap();
+
+    let guard = monitor.lock_blocking();
+    assert_eq!(guard.0, 10_000);
+    assert_eq!(
+        format!("{monitor:?}"),
+        "AsyncMonitor { data: <locked>, locked: true }"
+    );
+}
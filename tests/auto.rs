@@ -0,0 +1,70 @@
+#![cfg(feature = "std")]
+
+use parking_monitor::AutoMonitor;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+// Spawns a thread blocked in `await_condition` until the value is 2, with a
+// predicate that panics on 1. Returns once the predicate is registered.
+fn spawn_waiter(monitor: &Arc<AutoMonitor<i32>>) -> thread::JoinHandle<()> {
+    let registered = Arc::new(AtomicBool::new(false));
+    let handle = {
+        let monitor = Arc::clone(monitor);
+        let registered = Arc::clone(&registered);
+        thread::spawn(move || {
+            let mut guard = monitor.lock();
+            guard.await_condition(|&v| {
+                registered.store(true, Ordering::Relaxed);
+                assert_ne!(v, 1, "predicate panicked");
+                v == 2
+            });
+        })
+    };
+    // The first evaluation happens under the lock, which is only given up
+    // once the predicate has been queued.
+    while !registered.load(Ordering::Relaxed) {
+        thread::yield_now();
+    }
+    handle
+}
+
+#[test]
+fn predicate_wakes_waiter() {
+    let monitor = Arc::new(AutoMonitor::new(0));
+    let waiter = spawn_waiter(&monitor);
+    *monitor.lock() = 2;
+    waiter.join().unwrap();
+}
+
+#[test]
+fn panicking_predicate_panics_on_its_own_thread() {
+    let monitor = Arc::new(AutoMonitor::new(0));
+    let waiter = spawn_waiter(&monitor);
+
+    *monitor.lock() = 1;
+    assert!(waiter.join().is_err());
+    assert_eq!(*monitor.try_lock().expect("lock left held"), 1);
+}
+
+#[test]
+fn panicking_predicate_during_unwind_does_not_abort() {
+    let monitor = Arc::new(AutoMonitor::new(0));
+    let waiter = spawn_waiter(&monitor);
+
+    let unwinding = {
+        let monitor = Arc::clone(&monitor);
+        thread::spawn(move || {
+            let mut guard = monitor.lock();
+            *guard = 1;
+            panic!("releasing thread panicked");
+        })
+    };
+    assert!(unwinding.join().is_err());
+    assert!(waiter.join().is_err());
+    assert_eq!(*monitor.try_lock().expect("lock left held"), 1);
+}
@@ -0,0 +1,98 @@
+// Runs with or without the `std` feature: the spin backend below brings its own
+// clock, which is all timed waits need from it when the crate is `no_std`.
+
+mod common;
+
+use common::await_waiters;
+use lock_api::RawMutex as _;
+use parking_monitor::{
+    set_backend, Backend, Instant, Monitor, RawSpinMutex, SetBackendError, SignalMode, WaitOutcome,
+};
+use std::{
+    sync::{Arc, Once, OnceLock},
+    thread,
+    time::Duration,
+};
+
+struct Spin;
+
+impl Backend for Spin {
+    fn park(&self, _key: usize, validate: &dyn Fn() -> bool, _deadline: Option<Instant>) {
+        if validate() {
+            thread::yield_now();
+        }
+    }
+
+    fn unpark(&self, _key: usize) {}
+
+    fn now(&self) -> Duration {
+        static EPOCH: OnceLock<std::time::Instant> = OnceLock::new();
+        EPOCH.get_or_init(std::time::Instant::now).elapsed()
+    }
+}
+
+static SPIN: Spin = Spin;
+
+// The backend is global, so every test installs it before touching a monitor.
+fn install() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| set_backend(&SPIN).unwrap());
+}
+
+type SpinMonitor<T> = Monitor<T, usize, RawSpinMutex>;
+
+fn spin_monitor<T>(t: T) -> Arc<SpinMonitor<T>> {
+    Arc::new(Monitor::from_raw(RawSpinMutex::INIT, t))
+}
+
+#[test]
+fn backend_can_only_be_set_once() {
+    install();
+    assert_eq!(set_backend(&SPIN), Err(SetBackendError));
+}
+
+#[test]
+fn timed_wait_times_out() {
+    install();
+    let monitor = spin_monitor(());
+    let start = std::time::Instant::now();
+    assert_eq!(
+        monitor.lock().wait_for(Duration::from_millis(20)),
+        WaitOutcome::TimedOut
+    );
+    assert!(start.elapsed() >= Duration::from_millis(20));
+    assert_eq!(monitor.waiter_count(), 0);
+}
+
+#[test]
+fn notify_wakes_timed_waiter() {
+    install();
+    for mode in [SignalMode::Continue, SignalMode::UrgentWait] {
+        let monitor = spin_monitor(0);
+        let waiter = {
+            let monitor = Arc::clone(&monitor);
+            thread::spawn(move || {
+                let mut guard = monitor.lock();
+                let outcome = guard.wait_while_for(Duration::from_secs(10), |v| *v == 0);
+                *guard += 1;
+                outcome
+            })
+        };
+        await_waiters(&monitor, 1);
+
+        let mut guard = monitor.lock();
+        guard.set_signal_mode(mode);
+        *guard = 1;
+        assert_eq!(guard.notify_one(), 1);
+        if mode == SignalMode::UrgentWait {
+            assert_eq!(*guard, 2);
+        }
+        drop(guard);
+
+        let outcome = waiter.join().unwrap();
+        assert!(
+            matches!(outcome, WaitOutcome::PredicateSatisfied { .. }),
+            "{mode:?}: {outcome:?}"
+        );
+    }
+}
@@ -0,0 +1,17 @@
+#![cfg(feature = "std")]
+
+use parking_lot::RawFairMutex;
+use parking_monitor::Monitor;
+
+#[test]
+fn builders_apply_to_any_monitor() {
+    let monitor = Monitor::from_raw(<RawFairMutex as lock_api::RawMutex>::INIT, ())
+        .named("queue")
+        .leveled(3);
+    assert_eq!(monitor.name(), Some("queue"));
+    assert_eq!(monitor.level(), Some(3));
+
+    let monitor = Monitor::new(0);
+    assert_eq!(monitor.name(), None);
+    assert_eq!(monitor.level(), None);
+}
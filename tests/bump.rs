@@ -0,0 +1,85 @@
+#![cfg(feature = "std")]
+
+use parking_monitor::{Monitor, MonitorGuard};
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+// Holds the lock while another thread queues on it, bumping until the other
+// thread's update shows up.
+fn admits_queued_locker(bump: fn(&mut MonitorGuard<'_, Vec<u32>>)) {
+    let monitor = Arc::new(Monitor::new(Vec::new()));
+    let mut guard = monitor.lock();
+    let locker = {
+        let monitor = Arc::clone(&monitor);
+        thread::spawn(move || monitor.lock().push(1))
+    };
+    while guard.is_empty() {
+        thread::sleep(Duration::from_millis(1));
+        bump(&mut guard);
+    }
+    assert!(monitor.is_locked());
+    drop(guard);
+    locker.join().unwrap();
+    assert!(!monitor.is_locked());
+}
+
+#[test]
+fn bump_admits_queued_locker() {
+    admits_queued_locker(|guard| guard.bump());
+}
+
+#[test]
+fn bump_fair_admits_queued_locker() {
+    admits_queued_locker(|guard| guard.bump_fair());
+}
+
+#[test]
+fn unlocked_releases_for_the_closure() {
+    let monitor = Monitor::new(1);
+    let mut guard = monitor.lock();
+    let seen = guard.unlocked(|| {
+        let mut other = monitor.try_lock().unwrap();
+        *other += 1;
+        *other
+    });
+    assert_eq!(seen, 2);
+    assert!(monitor.is_locked());
+    assert_eq!(*guard, 2);
+    drop(guard);
+    assert!(!monitor.is_locked());
+}
+
+// Records whether the monitor is locked when dropped, which during unwinding
+// is after `unlocked` has returned and before the guard is dropped.
+struct Probe<'a>(&'a Monitor<()>, &'a AtomicBool);
+
+impl Drop for Probe<'_> {
+    fn drop(&mut self) {
+        self.1.store(self.0.is_locked(), Ordering::Relaxed);
+    }
+}
+
+#[test]
+fn unlocked_relocks_when_closure_panics() {
+    let monitor = Monitor::new(());
+    let relocked = AtomicBool::new(false);
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut guard = monitor.lock();
+        let _probe = Probe(&monitor, &relocked);
+        guard.unlocked(|| {
+            assert!(!monitor.is_locked());
+            panic!("closure panicked");
+        })
+    }));
+    assert!(result.is_err());
+    assert!(relocked.load(Ordering::Relaxed));
+    assert!(!monitor.is_locked());
+    assert!(monitor.try_lock().is_some());
+}
@@ -0,0 +1,119 @@
+#![cfg(feature = "std")]
+
+mod common;
+
+use common::await_waiters;
+use parking_monitor::{CancellationToken, Monitor, SignalMode, WaitOutcome};
+use std::{
+    sync::{Arc, Barrier},
+    thread,
+    time::{Duration, Instant},
+};
+
+#[test]
+fn cancel_wakes_waiters_on_every_monitor() {
+    let token = CancellationToken::new();
+    let monitors: Vec<_> = (0..3).map(|_| Arc::new(Monitor::new(()))).collect();
+    let waiters: Vec<_> = monitors
+        .iter()
+        .map(|monitor| {
+            let monitor = Arc::clone(monitor);
+            let token = token.clone();
+            thread::spawn(move || monitor.lock().wait_cancellable(&token))
+        })
+        .collect();
+    for monitor in &monitors {
+        await_waiters(monitor, 1);
+    }
+
+    token.cancel();
+    for waiter in waiters {
+        assert!(waiter.join().unwrap().is_cancelled());
+    }
+    assert!(monitors.iter().all(|m| m.waiter_count() == 0));
+}
+
+#[test]
+fn cancelled_token_returns_at_once() {
+    let token = CancellationToken::new();
+    token.cancel();
+    let monitor = Monitor::new(());
+    assert!(monitor.lock().wait_cancellable(&token).is_cancelled());
+    assert_eq!(monitor.waiter_count(), 0);
+}
+
+#[test]
+fn predicate_gets_a_last_look_after_cancel() {
+    let token = CancellationToken::new();
+    let monitor = Arc::new(Monitor::new(false));
+    let waiter = {
+        let monitor = Arc::clone(&monitor);
+        let token = token.clone();
+        thread::spawn(move || {
+            monitor
+                .lock()
+                .wait_while_cancellable(&token, |ready| !*ready)
+        })
+    };
+    await_waiters(&monitor, 1);
+
+    let mut guard = monitor.lock();
+    *guard = true;
+    token.cancel();
+    drop(guard);
+
+    assert!(matches!(
+        waiter.join().unwrap(),
+        WaitOutcome::PredicateSatisfied { .. }
+    ));
+}
+
+// Exactly one of a racing notify and cancel reaches the waiter, and a notify
+// that reports a wakeup is never swallowed by the cancellation.
+#[test]
+fn cancel_racing_notify() {
+    for mode in [
+        SignalMode::Continue,
+        SignalMode::Wait,
+        SignalMode::UrgentWait,
+    ] {
+        for round in 0..200 {
+            let token = CancellationToken::new();
+            let monitor = Arc::new(Monitor::new(()));
+            let start = Arc::new(Barrier::new(2));
+
+            let waiter = {
+                let monitor = Arc::clone(&monitor);
+                let token = token.clone();
+                thread::spawn(move || monitor.lock().wait_cancellable(&token))
+            };
+            await_waiters(&monitor, 1);
+
+            let canceller = {
+                let token = token.clone();
+                let start = Arc::clone(&start);
+                thread::spawn(move || {
+                    start.wait();
+                    token.cancel();
+                })
+            };
+            let mut guard = monitor.lock();
+            guard.set_signal_mode(mode);
+            start.wait();
+            // The canceller is still waking from the barrier, so give it a
+            // head start of varying length.
+            let pause = Instant::now() + Duration::from_micros(round % 20 * 5);
+            while Instant::now() < pause {}
+            let notified = guard.notify_one();
+            drop(guard);
+
+            canceller.join().unwrap();
+            let outcome = waiter.join().unwrap();
+            match notified {
+                1 => assert!(matches!(outcome, WaitOutcome::Notified { .. }), "{mode:?}"),
+                _ => assert!(outcome.is_cancelled(), "{mode:?}"),
+            }
+            assert_eq!(monitor.waiter_count(), 0);
+        }
+    }
+}
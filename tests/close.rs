@@ -0,0 +1,91 @@
+#![cfg(feature = "std")]
+
+mod common;
+
+use common::await_waiters;
+use parking_monitor::{Monitor, Select, WaitOutcome};
+use std::{sync::Arc, thread, time::Duration};
+
+#[test]
+fn close_wakes_waiters_on_every_condition() {
+    let monitor = Arc::new(Monitor::with_conditions(false, 2));
+    let untimed = {
+        let monitor = Arc::clone(&monitor);
+        thread::spawn(move || monitor.lock().wait_while_on(1, |ready| !*ready))
+    };
+    let timed = {
+        let monitor = Arc::clone(&monitor);
+        thread::spawn(move || monitor.lock().wait_for(Duration::from_secs(10)))
+    };
+    await_waiters(&monitor, 2);
+
+    assert_eq!(monitor.lock().close(), 2);
+    assert!(monitor.is_closed());
+    assert_eq!(
+        untimed.join().unwrap(),
+        WaitOutcome::Closed { remaining: None }
+    );
+    let outcome = timed.join().unwrap();
+    assert!(outcome.is_closed());
+    assert!(outcome.remaining().is_some_and(|r| r > Duration::ZERO));
+}
+
+#[test]
+fn waits_after_close_return_at_once() {
+    let monitor = Monitor::new(false);
+    let mut guard = monitor.lock();
+    assert_eq!(guard.close(), 0);
+    assert!(guard.wait().is_closed());
+    assert!(guard.wait_while(|ready| !*ready).is_closed());
+    *guard = true;
+    assert!(matches!(
+        guard.wait_while(|ready| !*ready),
+        WaitOutcome::PredicateSatisfied { remaining: None }
+    ));
+}
+
+// The select is parked on both monitors when the second is closed, and has to
+// give up rather than re-register on it forever.
+#[test]
+fn close_ends_a_parked_select() {
+    let monitors = [Arc::new(Monitor::new(false)), Arc::new(Monitor::new(false))];
+    let closer = {
+        let monitors = monitors.clone();
+        thread::spawn(move || {
+            monitors.iter().for_each(|m| await_waiters(m, 1));
+            assert_eq!(monitors[1].lock().close(), 1);
+        })
+    };
+
+    let mut select = Select::new();
+    for monitor in &monitors {
+        select.wait(monitor, |ready| *ready);
+    }
+    let closed = select.ready().unwrap_err();
+    assert_eq!(closed.index(), 1);
+    closer.join().unwrap();
+    for monitor in &monitors {
+        assert!(!monitor.is_locked());
+        assert_eq!(monitor.waiter_count(), 0);
+    }
+
+    // A closed case still wins once its predicate holds.
+    *monitors[1].lock() = true;
+    assert_eq!(select.ready().unwrap().index(), 1);
+    assert!(select.ready_for(Duration::from_millis(10)).is_ok());
+}
+
+#[test]
+fn select_on_a_closed_monitor_returns_at_once() {
+    let monitor = Monitor::new(false);
+    monitor.lock().close();
+    let mut select = Select::new();
+    select.wait(&monitor, |ready| *ready);
+    assert_eq!(
+        select
+            .ready_for(Duration::from_secs(10))
+            .unwrap_err()
+            .index(),
+        0
+    );
+}
@@ -0,0 +1,16 @@
+// Helpers shared by the integration tests. Each test binary only uses some.
+#![allow(dead_code)]
+
+use parking_monitor::{ConditionKey, Monitor};
+use std::thread;
+
+// Blocks until `n` threads are parked on the monitor, so that a notify is
+// known to reach them.
+pub fn await_waiters<T, C: ConditionKey, R: lock_api::RawMutex>(
+    monitor: &Monitor<T, C, R>,
+    n: usize,
+) {
+    while monitor.waiter_count() < n {
+        thread::yield_now();
+    }
+}
@@ -0,0 +1,70 @@
+#![cfg(feature = "deadlock_detection")]
+
+mod common;
+
+use common::await_waiters;
+use parking_monitor::{deadlock, Monitor};
+use std::{
+    sync::{Arc, Barrier},
+    thread,
+    time::{Duration, Instant},
+};
+
+fn lock_both(
+    first: &Arc<Monitor<()>>,
+    second: &Arc<Monitor<()>>,
+    barrier: &Arc<Barrier>,
+    name: &str,
+) {
+    let (first, second, barrier) = (Arc::clone(first), Arc::clone(second), Arc::clone(barrier));
+    thread::Builder::new()
+        .name(name.into())
+        .spawn(move || {
+            let _first = first.lock();
+            barrier.wait();
+            let _second = second.lock();
+        })
+        .unwrap();
+}
+
+// The detector is global, so both halves run in one test: a waiter whose
+// notifier has not touched a monitor yet must not show up while the cycle is
+// still to come.
+#[test]
+fn reports_lock_cycles_but_not_waiters() {
+    let monitor = Arc::new(Monitor::new(false));
+    let waiter = {
+        let monitor = Arc::clone(&monitor);
+        thread::spawn(move || {
+            monitor.lock().wait_while(|ready| !*ready);
+        })
+    };
+    await_waiters(&monitor, 1);
+    assert!(deadlock::check().is_empty());
+    let mut guard = monitor.lock();
+    *guard = true;
+    guard.notify_one();
+    drop(guard);
+    waiter.join().unwrap();
+
+    // These two threads are left deadlocked for good.
+    let (a, b) = (Arc::new(Monitor::new(())), Arc::new(Monitor::new(())));
+    let barrier = Arc::new(Barrier::new(2));
+    lock_both(&a, &b, &barrier, "first");
+    lock_both(&b, &a, &barrier, "second");
+
+    let give_up = Instant::now() + Duration::from_secs(10);
+    let deadlocks = loop {
+        let deadlocks = deadlock::check();
+        if !deadlocks.is_empty() || Instant::now() > give_up {
+            break deadlocks;
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+    assert_eq!(deadlocks.len(), 1);
+    let deadlock::Deadlock::LockCycle(threads) = &deadlocks[0];
+    let mut names: Vec<_> = threads.iter().map(|t| t.name().unwrap()).collect();
+    names.sort_unstable();
+    assert_eq!(names, ["first", "second"]);
+    assert!(threads.iter().all(|t| !t.backtrace().is_empty()));
+}
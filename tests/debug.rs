@@ -0,0 +1,59 @@
+#![cfg(feature = "std")]
+
+mod common;
+
+use common::await_waiters;
+use parking_monitor::Monitor;
+use std::{
+    fmt,
+    panic::{self, AssertUnwindSafe},
+    sync::Arc,
+    thread,
+    time::Duration,
+};
+
+#[test]
+fn debug_shows_data_and_waiters() {
+    let monitor = Arc::new(Monitor::with_conditions(7, 2).named("jobs"));
+    assert_eq!(
+        format!("{monitor:?}"),
+        r#"Monitor { name: "jobs", data: 7, locked: false, waiters: 0, condition_waiters: [0, 0] }"#
+    );
+
+    let waiter = {
+        let monitor = Arc::clone(&monitor);
+        thread::spawn(move || {
+            monitor.lock().wait_on(1);
+        })
+    };
+    await_waiters(&monitor, 1);
+    assert_eq!(monitor.waiter_count_on(0), 0);
+    assert_eq!(monitor.waiter_count_on(1), 1);
+
+    let mut guard = monitor.lock();
+    assert!(monitor.is_locked());
+    assert_eq!(
+        format!("{monitor:?}"),
+        r#"Monitor { name: "jobs", data: <locked>, locked: true, waiters: 0, condition_waiters: [0, 1] }"#
+    );
+    assert_eq!(guard.notify_one_on(1), 1);
+    drop(guard);
+    waiter.join().unwrap();
+    assert!(!monitor.is_locked());
+}
+
+struct Panics;
+
+impl fmt::Debug for Panics {
+    fn fmt(&self, _: &mut fmt::Formatter<'_>) -> fmt::Result {
+        panic!("formatting panicked")
+    }
+}
+
+#[test]
+fn debug_unlocks_when_formatting_panics() {
+    let monitor = Monitor::new(Panics);
+    assert!(panic::catch_unwind(AssertUnwindSafe(|| format!("{monitor:?}"))).is_err());
+    assert!(!monitor.is_locked());
+    assert!(monitor.try_lock_for(Duration::from_millis(50)).is_some());
+}
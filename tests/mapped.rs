@@ -0,0 +1,155 @@
+#![cfg(feature = "std")]
+
+mod common;
+
+use common::await_waiters;
+use parking_monitor::{CancellationToken, Monitor, SignalMode, WaitOutcome};
+use std::{sync::Arc, thread, time::Duration};
+
+// The woken waiter replaces the vector while it holds the lock lent by the
+// notify, so the mapped guard has to project into the new one.
+#[test]
+fn hoare_notify_reprojects() {
+    for mode in [SignalMode::Wait, SignalMode::UrgentWait] {
+        let monitor = Arc::new(Monitor::new(vec![0]));
+        let waiter = {
+            let monitor = Arc::clone(&monitor);
+            thread::spawn(move || {
+                let mut guard = monitor.lock();
+                guard.wait();
+                *guard = vec![42; 64];
+            })
+        };
+        await_waiters(&monitor, 1);
+
+        let mut mapped = monitor.lock().map(|v| &mut v[0]);
+        mapped.set_signal_mode(mode);
+        let (notified, Ok(mapped)) = mapped.notify_one() else {
+            panic!("infallible projection failed");
+        };
+        assert_eq!(notified, 1);
+        assert_eq!(*mapped, 42, "{mode:?}");
+        drop(mapped);
+        waiter.join().unwrap();
+    }
+}
+
+#[test]
+fn mapped_predicate_waits_on_a_condition() {
+    let monitor = Arc::new(Monitor::with_conditions((0, false), 1));
+    let setter = {
+        let monitor = Arc::clone(&monitor);
+        thread::spawn(move || {
+            await_waiters(&monitor, 1);
+            let mut guard = monitor.lock();
+            guard.1 = true;
+            guard.notify_all_on(0);
+        })
+    };
+
+    let mapped = monitor.lock().map(|(_, ready)| ready);
+    let (outcome, Ok(mapped)) = mapped.wait_while_on_with_priority(0, 1, |ready| !*ready) else {
+        panic!("infallible projection failed");
+    };
+    assert!(matches!(outcome, WaitOutcome::PredicateSatisfied { .. }));
+    assert!(*mapped);
+    let (outcome, mapped) = mapped.wait_while_on_for(0, Duration::from_millis(10), |ready| *ready);
+    assert_eq!(outcome, WaitOutcome::TimedOut);
+    assert!(mapped.is_ok());
+    drop(mapped);
+    setter.join().unwrap();
+}
+
+#[test]
+fn mapped_wait_is_cancellable() {
+    let token = CancellationToken::new();
+    let monitor = Arc::new(Monitor::new((0, 0)));
+    let canceller = {
+        let monitor = Arc::clone(&monitor);
+        let token = token.clone();
+        thread::spawn(move || {
+            await_waiters(&monitor, 1);
+            token.cancel();
+        })
+    };
+
+    let mapped = monitor.lock().map(|(_, b)| b);
+    let (outcome, mapped) = mapped.wait_while_cancellable(&token, |b| *b == 0);
+    assert!(outcome.is_cancelled());
+    assert!(mapped.is_ok());
+    canceller.join().unwrap();
+}
+
+// The notifier clears the option the guard was projected into, so the waiter
+// gets the plain guard back instead of a dangling projection.
+#[test]
+fn failed_reprojection_hands_back_the_guard() {
+    let monitor = Arc::new(Monitor::new(Some(1)));
+    let notifier = {
+        let monitor = Arc::clone(&monitor);
+        thread::spawn(move || {
+            await_waiters(&monitor, 1);
+            let mut guard = monitor.lock();
+            *guard = None;
+            guard.notify_one();
+        })
+    };
+
+    let Ok(mapped) = monitor.lock().try_map(|o| o.as_mut()) else {
+        panic!("projection failed before waiting");
+    };
+    let (outcome, remapped) = mapped.wait();
+    assert!(matches!(outcome, WaitOutcome::Notified { .. }));
+    let Err(guard) = remapped else {
+        panic!("projected into a cleared option");
+    };
+    assert_eq!(*guard, None);
+    drop(guard);
+    notifier.join().unwrap();
+}
+
+// A predicate wait stops as soon as the projection fails rather than waiting
+// on a value that is gone.
+#[test]
+fn failed_reprojection_ends_predicate_wait() {
+    let monitor = Arc::new(Monitor::new(Some(0)));
+    let notifier = {
+        let monitor = Arc::clone(&monitor);
+        thread::spawn(move || {
+            await_waiters(&monitor, 1);
+            let mut guard = monitor.lock();
+            *guard = None;
+            guard.notify_one();
+        })
+    };
+
+    let Ok(mapped) = monitor.lock().try_map(|o| o.as_mut()) else {
+        panic!("projection failed before waiting");
+    };
+    let (_, remapped) = mapped.wait_while(|v| *v == 0);
+    assert!(remapped.is_err());
+    notifier.join().unwrap();
+}
+
+#[test]
+fn failed_reprojection_after_hoare_notify() {
+    let monitor = Arc::new(Monitor::new(Some(1)));
+    let waiter = {
+        let monitor = Arc::clone(&monitor);
+        thread::spawn(move || {
+            let mut guard = monitor.lock();
+            guard.wait();
+            *guard = None;
+        })
+    };
+    await_waiters(&monitor, 1);
+
+    let Ok(mut mapped) = monitor.lock().try_map(|o| o.as_mut()) else {
+        panic!("projection failed before notifying");
+    };
+    mapped.set_signal_mode(SignalMode::Wait);
+    let (notified, remapped) = mapped.notify_one();
+    assert_eq!(notified, 1);
+    assert!(remapped.is_err());
+    waiter.join().unwrap();
+}
@@ -0,0 +1,68 @@
+#![cfg(feature = "std")]
+
+mod common;
+
+use common::await_waiters;
+use parking_monitor::Monitor;
+use std::{sync::Arc, thread};
+
+fn spawn_waiters(
+    monitor: &Arc<Monitor<usize>>,
+    cond: Option<usize>,
+    n: usize,
+) -> Vec<thread::JoinHandle<()>> {
+    (0..n)
+        .map(|_| {
+            let monitor = Arc::clone(monitor);
+            thread::spawn(move || {
+                let mut guard = monitor.lock();
+                let outcome = match cond {
+                    Some(cond) => guard.wait_on(cond),
+                    None => guard.wait(),
+                };
+                assert!(!outcome.timed_out());
+                *guard += 1;
+            })
+        })
+        .collect()
+}
+
+#[test]
+fn notify_n_wakes_at_most_n() {
+    let monitor = Arc::new(Monitor::with_conditions(0, 1));
+    let waiters = spawn_waiters(&monitor, None, 5);
+    await_waiters(&monitor, 5);
+
+    // Nobody waits on the condition.
+    assert_eq!(monitor.lock().notify_n_on(0, 3), 0);
+    assert_eq!(monitor.lock().notify_n(3), 3);
+    while *monitor.lock() < 3 {
+        thread::yield_now();
+    }
+    assert_eq!(monitor.waiter_count(), 2);
+
+    assert_eq!(monitor.lock().notify_n(5), 2);
+    for waiter in waiters {
+        waiter.join().unwrap();
+    }
+    assert_eq!(*monitor.lock(), 5);
+    assert_eq!(monitor.lock().notify_n(1), 0);
+}
+
+#[test]
+fn notify_n_counts_only_woken_waiters() {
+    let monitor = Arc::new(Monitor::with_conditions(0, 1));
+    let waiters = spawn_waiters(&monitor, Some(0), 2);
+    await_waiters(&monitor, 2);
+
+    let mut guard = monitor.lock();
+    assert_eq!(guard.notify_n_on(0, 0), 0);
+    assert_eq!(guard.notify_n_on(0, 10), 2);
+    assert_eq!(guard.notify_all_on(0), 0);
+    drop(guard);
+    for waiter in waiters {
+        waiter.join().unwrap();
+    }
+    assert_eq!(*monitor.lock(), 2);
+    assert_eq!(monitor.waiter_count_on(0), 0);
+}
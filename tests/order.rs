@@ -0,0 +1,38 @@
+#![cfg(all(feature = "std", debug_assertions))]
+
+use parking_lot::RawFairMutex;
+use parking_monitor::{ConditionKey, Monitor};
+
+#[derive(Clone, Copy)]
+enum Cond {
+    Ready,
+}
+
+impl ConditionKey for Cond {
+    const COUNT: usize = 1;
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+#[test]
+fn leveled_applies_to_any_monitor() {
+    let outer = Monitor::<_, Cond>::keyed(()).leveled(1);
+    let inner = Monitor::from_raw(<RawFairMutex as lock_api::RawMutex>::INIT, ()).leveled(2);
+    assert_eq!(outer.level(), Some(1));
+    assert_eq!(inner.level(), Some(2));
+
+    let mut outer = outer.lock();
+    let _inner = inner.lock();
+    assert_eq!(outer.notify_one_on(Cond::Ready), 0);
+}
+
+#[test]
+#[should_panic(expected = "lock order violation")]
+fn locking_out_of_order_panics() {
+    let outer = Monitor::new(()).leveled(1);
+    let inner = Monitor::new(()).leveled(2);
+    let _inner = inner.lock();
+    let _outer = outer.lock();
+}
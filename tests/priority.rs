@@ -0,0 +1,69 @@
+#![cfg(feature = "std")]
+
+mod common;
+
+use common::await_waiters;
+use parking_monitor::{Monitor, WaitOutcome};
+use std::{sync::Arc, thread};
+
+// Waiters queue one at a time, so their arrival order is known, then are
+// woken one by one and record themselves.
+#[test]
+fn notify_one_wakes_highest_priority_first() {
+    let priorities = [0, 5, 0, 5, -1, 10];
+    let monitor = Arc::new(Monitor::new(Vec::new()));
+    let waiters: Vec<_> = priorities
+        .iter()
+        .enumerate()
+        .map(|(id, &priority)| {
+            let waiter = {
+                let monitor = Arc::clone(&monitor);
+                thread::spawn(move || {
+                    let mut guard = monitor.lock();
+                    let outcome = guard.wait_with_priority(priority);
+                    guard.push(id);
+                    outcome
+                })
+            };
+            await_waiters(&monitor, id + 1);
+            waiter
+        })
+        .collect();
+
+    for woken in 1..=priorities.len() {
+        assert_eq!(monitor.lock().notify_one(), 1);
+        while monitor.lock().len() < woken {
+            thread::yield_now();
+        }
+    }
+    for waiter in waiters {
+        assert!(matches!(
+            waiter.join().unwrap(),
+            WaitOutcome::Notified { .. }
+        ));
+    }
+    assert_eq!(*monitor.lock(), [5, 1, 3, 0, 2, 4]);
+}
+
+#[test]
+fn priorities_are_per_condition() {
+    let monitor = Arc::new(Monitor::with_conditions(Vec::new(), 2));
+    let spawn = |cond: usize, priority: i32, id: usize| {
+        let monitor = Arc::clone(&monitor);
+        thread::spawn(move || {
+            let mut guard = monitor.lock();
+            guard.wait_on_with_priority(cond, priority);
+            guard.push(id);
+        })
+    };
+    let low = spawn(0, 1, 0);
+    await_waiters(&monitor, 1);
+    let high = spawn(1, 9, 1);
+    await_waiters(&monitor, 2);
+
+    assert_eq!(monitor.lock().notify_one_on(0), 1);
+    low.join().unwrap();
+    assert_eq!(monitor.lock().notify_one_on(1), 1);
+    high.join().unwrap();
+    assert_eq!(*monitor.lock(), [0, 1]);
+}
@@ -0,0 +1,41 @@
+#![cfg(feature = "std")]
+
+use parking_monitor::{ReentrantMonitor, WaitOutcome};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+// A wait on the inner guard gives up every level this thread holds, and the
+// outer guard sees the other thread's update afterwards.
+#[test]
+fn nested_wait_releases_every_level() {
+    let monitor = Arc::new(ReentrantMonitor::new(AtomicUsize::new(0)));
+    let waiter = {
+        let monitor = Arc::clone(&monitor);
+        thread::spawn(move || {
+            let outer = monitor.lock();
+            let mut inner = monitor.lock();
+            let outcome = inner.wait_while(|v| v.fetch_max(1, Ordering::Relaxed) < 2);
+            assert_eq!(outcome, WaitOutcome::PredicateSatisfied { remaining: None });
+            drop(inner);
+            outer.load(Ordering::Relaxed)
+        })
+    };
+
+    // The first check runs with both levels held, so once it has run this
+    // lock only succeeds while the waiter is parked.
+    while monitor.lock().load(Ordering::Relaxed) == 0 {
+        thread::yield_now();
+    }
+    let mut guard = monitor.lock();
+    guard.store(2, Ordering::Relaxed);
+    assert_eq!(guard.notify(), 1);
+    drop(guard);
+
+    assert_eq!(waiter.join().unwrap(), 2);
+    assert!(!monitor.is_locked());
+}
@@ -0,0 +1,160 @@
+#![cfg(feature = "std")]
+
+use parking_monitor::{RwMonitor, WaitOutcome};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+// Spawns readers blocked until the value is non-zero. Each bumps `checked` on
+// its first look, which happens under the shared lock, so once all have looked
+// a writer only gets in when every one of them is parked.
+fn spawn_readers(
+    monitor: &Arc<RwMonitor<usize>>,
+    n: usize,
+) -> Vec<thread::JoinHandle<(WaitOutcome, usize)>> {
+    let checked = Arc::new(AtomicUsize::new(0));
+    let readers = (0..n)
+        .map(|_| {
+            let monitor = Arc::clone(monitor);
+            let checked = Arc::clone(&checked);
+            thread::spawn(move || {
+                let mut guard = monitor.read();
+                let mut first = true;
+                let outcome = guard.wait_while(|v| {
+                    if std::mem::take(&mut first) {
+                        checked.fetch_add(1, Ordering::Relaxed);
+                    }
+                    *v == 0
+                });
+                (outcome, *guard)
+            })
+        })
+        .collect();
+    while checked.load(Ordering::Relaxed) < n {
+        thread::yield_now();
+    }
+    readers
+}
+
+#[test]
+fn readers_wait_until_writer_notifies() {
+    let monitor = Arc::new(RwMonitor::new(0));
+    let readers = spawn_readers(&monitor, 3);
+
+    let mut guard = monitor.write();
+    *guard = 7;
+    assert_eq!(guard.notify_all(), 3);
+    drop(guard);
+
+    for reader in readers {
+        assert_eq!(
+            reader.join().unwrap(),
+            (WaitOutcome::PredicateSatisfied { remaining: None }, 7)
+        );
+    }
+}
+
+#[test]
+fn writer_waits_until_another_writer_notifies() {
+    let monitor = Arc::new(RwMonitor::new(0));
+    let waiter = {
+        let monitor = Arc::clone(&monitor);
+        thread::spawn(move || {
+            let mut guard = monitor.write();
+            let outcome = guard.wait_while(|v| *v == 0);
+            *guard += 1;
+            outcome
+        })
+    };
+
+    loop {
+        let mut guard = monitor.write();
+        *guard = 1;
+        if guard.notify_one() == 1 {
+            break;
+        }
+        drop(guard);
+        thread::yield_now();
+        // The waiter may have seen the value before parking.
+        if waiter.is_finished() {
+            break;
+        }
+    }
+    assert!(matches!(
+        waiter.join().unwrap(),
+        WaitOutcome::PredicateSatisfied { .. }
+    ));
+    assert_eq!(*monitor.read(), 2);
+}
+
+#[test]
+fn timed_out_waits_leave_the_queue() {
+    let monitor = RwMonitor::new(0);
+    assert_eq!(
+        monitor.read().wait_for(Duration::from_millis(10)),
+        WaitOutcome::TimedOut
+    );
+    assert_eq!(
+        monitor
+            .write()
+            .wait_while_for(Duration::from_millis(10), |v| *v == 0),
+        WaitOutcome::TimedOut
+    );
+    assert_eq!(monitor.write().notify_all(), 0);
+    assert!(!monitor.is_locked());
+}
+
+#[test]
+fn downgrade_keeps_the_value_and_admits_readers() {
+    let monitor = RwMonitor::new(0);
+    let mut guard = monitor.write();
+    *guard = 1;
+    let read = guard.downgrade();
+    assert_eq!(*read, 1);
+    assert!(!monitor.is_locked_exclusive());
+    assert_eq!(monitor.try_read().map(|g| *g), Some(1));
+    assert!(monitor.try_write().is_none());
+    drop(read);
+
+    let mut guard = monitor.write();
+    *guard = 2;
+    let upgradable = guard.downgrade_to_upgradable();
+    assert_eq!(*upgradable, 2);
+    assert!(monitor.try_read().is_some());
+    assert!(monitor.try_upgradable_read().is_none());
+
+    let mut guard = upgradable.upgrade();
+    *guard = 3;
+    assert!(monitor.try_read().is_none());
+    drop(guard);
+    assert!(!monitor.is_locked());
+}
+
+#[test]
+fn upgrade_waits_for_readers() {
+    let monitor = RwMonitor::new(0);
+    let upgradable = monitor.upgradable_read();
+    let reader = monitor.read();
+
+    let Err(upgradable) = upgradable.try_upgrade() else {
+        panic!("upgraded while a reader holds the lock");
+    };
+    let Err(upgradable) = upgradable.try_upgrade_for(Duration::from_millis(10)) else {
+        panic!("upgraded while a reader holds the lock");
+    };
+    assert_eq!(*reader, 0);
+    drop(reader);
+
+    let mut guard = upgradable.try_upgrade().ok().unwrap();
+    *guard = 1;
+    drop(guard);
+
+    let read = monitor.upgradable_read().downgrade();
+    assert!(monitor.try_upgradable_read().is_some());
+    assert_eq!(*read, 1);
+}
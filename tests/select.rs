@@ -0,0 +1,100 @@
+#![cfg(feature = "std")]
+
+mod common;
+
+use common::await_waiters;
+use parking_monitor::{Monitor, Select};
+use std::{sync::Arc, thread, time::Duration};
+
+fn monitors(n: usize) -> Vec<Arc<Monitor<bool>>> {
+    (0..n).map(|_| Arc::new(Monitor::new(false))).collect()
+}
+
+// A notify on a monitor whose predicate is still false wakes the select, which
+// goes back to waiting on all of them until one is actually ready.
+#[test]
+fn select_wakes_on_whichever_monitor_is_ready() {
+    let monitors = monitors(3);
+    let notifier = {
+        let monitors = monitors.clone();
+        thread::spawn(move || {
+            monitors.iter().for_each(|m| await_waiters(m, 1));
+            assert_eq!(monitors[0].lock().notify_one(), 1);
+
+            monitors.iter().for_each(|m| await_waiters(m, 1));
+            let mut guard = monitors[2].lock();
+            *guard = true;
+            guard.notify_one();
+        })
+    };
+
+    let mut select = Select::new();
+    for monitor in &monitors {
+        select.wait(monitor, |ready| *ready);
+    }
+    let selected = select.ready().unwrap();
+    assert_eq!(selected.index(), 2);
+    let guard = selected.guard(&monitors[2]);
+    assert!(*guard);
+    assert!(monitors[2].try_lock().is_none());
+    assert!(monitors[0].try_lock().is_some());
+    assert!(monitors[1].try_lock().is_some());
+    drop(guard);
+
+    notifier.join().unwrap();
+    assert!(monitors.iter().all(|m| m.waiter_count() == 0));
+}
+
+#[test]
+fn select_returns_a_ready_case_without_waiting() {
+    let monitors = monitors(2);
+    *monitors[1].lock() = true;
+
+    let mut select = Select::new();
+    for monitor in &monitors {
+        select.wait(monitor, |ready| *ready);
+    }
+    let selected = select.ready().unwrap();
+    assert_eq!(selected.index(), 1);
+    assert!(monitors[1].try_lock().is_none());
+    drop(selected);
+    assert!(monitors[1].try_lock().is_some());
+}
+
+#[test]
+fn select_times_out_with_nothing_held() {
+    let monitors = monitors(2);
+    let mut select = Select::new();
+    for monitor in &monitors {
+        select.wait(monitor, |ready| *ready);
+    }
+    assert!(select
+        .ready_for(Duration::from_millis(20))
+        .unwrap()
+        .is_none());
+    for monitor in &monitors {
+        assert!(monitor.try_lock().is_some());
+        assert_eq!(monitor.waiter_count(), 0);
+    }
+}
+
+#[test]
+fn select_waits_on_a_condition() {
+    let monitor = Arc::new(Monitor::with_conditions(false, 2));
+    let notifier = {
+        let monitor = Arc::clone(&monitor);
+        thread::spawn(move || {
+            await_waiters(&monitor, 1);
+            let mut guard = monitor.lock();
+            *guard = true;
+            // Nobody waits on the default condition.
+            assert_eq!(guard.notify_all(), 0);
+            assert_eq!(guard.notify_one_on(1), 1);
+        })
+    };
+
+    let mut select = Select::new();
+    select.wait_on(&monitor, 1, |ready| *ready);
+    assert!(*select.ready().unwrap().guard(&monitor));
+    notifier.join().unwrap();
+}
@@ -0,0 +1,47 @@
+#![cfg(all(feature = "std", feature = "serde"))]
+
+use parking_monitor::{ConditionKey, Monitor};
+
+#[derive(Clone, Copy)]
+enum Cond {
+    Empty,
+    Full,
+}
+
+impl ConditionKey for Cond {
+    const COUNT: usize = 2;
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+#[test]
+fn keyed_monitor_round_trips() {
+    let monitor = Monitor::<_, Cond>::keyed(vec![1, 2, 3]);
+    let json = serde_json::to_string(&monitor).unwrap();
+    assert_eq!(json, "[1,2,3]");
+
+    let monitor: Monitor<Vec<u32>, Cond> = serde_json::from_str(&json).unwrap();
+    assert_eq!(*monitor.lock(), [1, 2, 3]);
+    assert_eq!(monitor.waiter_count_on(Cond::Empty), 0);
+    let mut guard = monitor.lock();
+    assert_eq!(guard.notify_one_on(Cond::Full), 0);
+}
+
+// Only the data is written out, so what the monitor was built with beyond its
+// key type is gone after the round trip.
+#[test]
+fn round_trip_drops_name_level_and_conditions() {
+    let monitor = Monitor::with_conditions(7, 2).named("jobs").leveled(3);
+    let json = serde_json::to_string(&monitor).unwrap();
+    assert_eq!(json, "7");
+
+    let monitor: Monitor<i32> = serde_json::from_str(&json).unwrap();
+    assert_eq!(monitor.name(), None);
+    assert_eq!(monitor.level(), None);
+    assert_eq!(
+        format!("{monitor:?}"),
+        "Monitor { data: 7, locked: false, waiters: 0 }"
+    );
+}
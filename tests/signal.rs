@@ -0,0 +1,237 @@
+#![cfg(feature = "std")]
+
+mod common;
+
+use common::await_waiters;
+use parking_monitor::{Monitor, SignalMode, WaitOutcome};
+use std::{
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+#[test]
+fn mesa_signaller_keeps_the_lock() {
+    let monitor = Arc::new(Monitor::new(0));
+    let waiter = {
+        let monitor = Arc::clone(&monitor);
+        thread::spawn(move || {
+            let mut guard = monitor.lock();
+            assert!(matches!(guard.wait(), WaitOutcome::Notified { .. }));
+            *guard
+        })
+    };
+
+    await_waiters(&monitor, 1);
+    let mut guard = monitor.lock();
+    *guard = 1;
+    assert_eq!(guard.notify_one(), 1);
+    *guard = 2;
+    drop(guard);
+
+    assert_eq!(waiter.join().unwrap(), 2);
+}
+
+#[test]
+fn hoare_signaller_hands_over_the_lock() {
+    for mode in [SignalMode::Wait, SignalMode::UrgentWait] {
+        let monitor = Arc::new(Monitor::new(0));
+        let waiter = {
+            let monitor = Arc::clone(&monitor);
+            thread::spawn(move || {
+                let mut guard = monitor.lock();
+                assert!(matches!(guard.wait(), WaitOutcome::Notified { .. }));
+                let seen = *guard;
+                *guard = 3;
+                seen
+            })
+        };
+
+        await_waiters(&monitor, 1);
+        let mut guard = monitor.lock();
+        guard.set_signal_mode(mode);
+        *guard = 1;
+        assert_eq!(guard.notify_one(), 1);
+        assert_eq!(*guard, 3, "{mode:?}");
+        drop(guard);
+
+        assert_eq!(waiter.join().unwrap(), 1, "{mode:?}");
+    }
+}
+
+#[test]
+fn hoare_notify_without_waiters_keeps_the_lock() {
+    let monitor = Monitor::new(0);
+    let mut guard = monitor.lock();
+    guard.set_signal_mode(SignalMode::UrgentWait);
+    *guard = 1;
+    assert_eq!(guard.notify_one(), 0);
+    assert_eq!(*guard, 1);
+}
+
+// The waiter's release goes to the urgent signaller ahead of a thread that
+// has been blocked in `lock` all along.
+#[test]
+fn urgent_signaller_resumes_before_entry_queue() {
+    let monitor = Arc::new(Monitor::new(Vec::new()));
+    let waiter = {
+        let monitor = Arc::clone(&monitor);
+        thread::spawn(move || {
+            let mut guard = monitor.lock();
+            guard.wait();
+            guard.push("waiter");
+        })
+    };
+    await_waiters(&monitor, 1);
+
+    let mut guard = monitor.lock();
+    let entrant = {
+        let monitor = Arc::clone(&monitor);
+        thread::spawn(move || monitor.lock().push("entrant"))
+    };
+    thread::sleep(Duration::from_millis(50));
+
+    guard.set_signal_mode(SignalMode::UrgentWait);
+    assert_eq!(guard.notify_one(), 1);
+    guard.push("signaller");
+    drop(guard);
+
+    waiter.join().unwrap();
+    entrant.join().unwrap();
+    assert_eq!(*monitor.lock(), ["waiter", "signaller", "entrant"]);
+}
+
+// With every signal handed over, a woken waiter finds its condition true
+// without re-checking it.
+#[test]
+fn hoare_condition_holds_when_waiter_runs() {
+    const NOT_EMPTY: usize = 0;
+    const NOT_FULL: usize = 1;
+    const THREADS: usize = 4;
+    const ITEMS: usize = 500;
+
+    let monitor = Arc::new(Monitor::with_conditions(None, 2));
+    let producers: Vec<_> = (0..THREADS)
+        .map(|p| {
+            let monitor = Arc::clone(&monitor);
+            thread::spawn(move || {
+                for i in 0..ITEMS {
+                    let mut guard = monitor.lock();
+                    guard.set_signal_mode(SignalMode::UrgentWait);
+                    if guard.is_some() {
+                        guard.wait_on(NOT_FULL);
+                        assert!(guard.is_none());
+                    }
+                    *guard = Some(p * ITEMS + i);
+                    guard.notify_one_on(NOT_EMPTY);
+                }
+            })
+        })
+        .collect();
+    let consumers: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let monitor = Arc::clone(&monitor);
+            thread::spawn(move || {
+                let mut taken = Vec::new();
+                for _ in 0..ITEMS {
+                    let mut guard = monitor.lock();
+                    guard.set_signal_mode(SignalMode::UrgentWait);
+                    if guard.is_none() {
+                        guard.wait_on(NOT_EMPTY);
+                        assert!(guard.is_some());
+                    }
+                    taken.push(guard.take().unwrap());
+                    guard.notify_one_on(NOT_FULL);
+                }
+                taken
+            })
+        })
+        .collect();
+
+    for producer in producers {
+        producer.join().unwrap();
+    }
+    let mut taken: Vec<_> = consumers
+        .into_iter()
+        .flat_map(|c| c.join().unwrap())
+        .collect();
+    taken.sort_unstable();
+    assert_eq!(taken, (0..THREADS * ITEMS).collect::<Vec<_>>());
+}
+
+// A notify that reports waking a waiter must be seen by exactly one wait as
+// `Notified`, however closely it races with that wait's timeout.
+#[test]
+fn timeout_racing_notify_is_never_lost() {
+    for mode in [
+        SignalMode::Continue,
+        SignalMode::Wait,
+        SignalMode::UrgentWait,
+    ] {
+        let monitor = Arc::new(Monitor::new(false));
+        let notifier = {
+            let monitor = Arc::clone(&monitor);
+            thread::spawn(move || {
+                let mut notified = 0;
+                loop {
+                    let mut guard = monitor.lock();
+                    if *guard {
+                        return notified;
+                    }
+                    guard.set_signal_mode(mode);
+                    notified += guard.notify_one();
+                    drop(guard);
+                    // Spread the notifications across the waits' timeouts.
+                    let pause = Instant::now() + Duration::from_micros(notified as u64 % 9 * 25);
+                    while Instant::now() < pause {
+                        thread::yield_now();
+                    }
+                }
+            })
+        };
+
+        let mut woken = 0;
+        let mut guard = monitor.lock();
+        for i in 0..1000 {
+            let timeout = Duration::from_micros(i % 10 * 20);
+            match guard.wait_for(timeout) {
+                WaitOutcome::Notified { remaining } => {
+                    assert!(remaining.is_some_and(|r| r <= timeout));
+                    woken += 1;
+                }
+                WaitOutcome::TimedOut => {}
+                outcome => panic!("unexpected {outcome:?}"),
+            }
+        }
+        *guard = true;
+        drop(guard);
+
+        assert_eq!(notifier.join().unwrap(), woken, "{mode:?}");
+    }
+}
+
+// A fair unlock by the waiter still goes to the urgent signaller first.
+#[test]
+fn urgent_signaller_resumes_after_fair_unlock() {
+    let monitor = Arc::new(Monitor::from_raw(
+        <parking_lot::RawFairMutex as lock_api::RawMutex>::INIT,
+        0,
+    ));
+    let waiter = {
+        let monitor = Arc::clone(&monitor);
+        thread::spawn(move || {
+            let mut guard = monitor.lock();
+            guard.wait();
+            *guard += 1;
+            guard.unlock_fair();
+        })
+    };
+    await_waiters(&monitor, 1);
+
+    let mut guard = monitor.lock();
+    guard.set_signal_mode(SignalMode::UrgentWait);
+    assert_eq!(guard.notify_one(), 1);
+    assert_eq!(*guard, 1);
+    drop(guard);
+    waiter.join().unwrap();
+}
@@ -0,0 +1,66 @@
+#![cfg(feature = "stats")]
+
+use parking_monitor::{Monitor, MonitorStats, WaitOutcome};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+#[test]
+fn stats_count_one_contended_lock_wait_and_notify() {
+    let monitor = Arc::new(Monitor::new(false));
+    let mut guard = monitor.lock();
+
+    let locking = Arc::new(AtomicBool::new(false));
+    let notifier = {
+        let monitor = Arc::clone(&monitor);
+        let locking = Arc::clone(&locking);
+        thread::spawn(move || {
+            locking.store(true, Ordering::Relaxed);
+            let mut guard = monitor.lock();
+            *guard = true;
+            assert_eq!(guard.notify_one(), 1);
+        })
+    };
+    // Give the notifier time to block on the lock before it is released.
+    while !locking.load(Ordering::Relaxed) {
+        thread::yield_now();
+    }
+    thread::sleep(Duration::from_millis(50));
+    assert!(matches!(
+        guard.wait_while(|ready| !*ready),
+        WaitOutcome::PredicateSatisfied { .. }
+    ));
+    drop(guard);
+    notifier.join().unwrap();
+
+    let stats = monitor.stats();
+    assert_eq!(stats.acquisitions(), 3);
+    // Taking the lock back after the wait may or may not find the notifier
+    // still holding it.
+    assert!((1..=2).contains(&stats.contended_acquisitions()));
+    assert_eq!(stats.blocked().count(), stats.contended_acquisitions());
+    assert_eq!(stats.held().count(), 3);
+    assert_eq!(stats.waits(), 1);
+    assert_eq!(stats.spurious_wakeups(), 0);
+    assert_eq!(stats.notifications(), 1);
+
+    // Formatting peeks at the data without counting as an acquisition.
+    assert_eq!(
+        format!("{monitor:?}"),
+        "Monitor { data: true, locked: false, waiters: 0 }"
+    );
+    assert_eq!(monitor.stats(), stats);
+
+    monitor.reset_stats();
+    assert_eq!(monitor.stats(), MonitorStats::default());
+    drop(monitor.lock());
+    let stats = monitor.stats();
+    assert_eq!(stats.acquisitions(), 1);
+    assert_eq!(stats.contended_acquisitions(), 0);
+    assert_eq!(stats.held().count(), 1);
+}
@@ -0,0 +1,93 @@
+#![cfg(feature = "tracing")]
+
+use parking_monitor::Monitor;
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex},
+};
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Subscriber,
+};
+use tracing_subscriber::{layer::Context, prelude::*, registry::LookupSpan, Layer};
+
+// A span or event as it was recorded: its name, or the name of the span it
+// happened in for events, and its fields formatted as strings.
+#[derive(Debug)]
+struct Record {
+    span: Option<&'static str>,
+    fields: HashMap<&'static str, String>,
+}
+
+#[derive(Default)]
+struct Fields(HashMap<&'static str, String>);
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name(), value.to_owned());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.insert(field.name(), format!("{value:?}"));
+    }
+}
+
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Vec<Record>>>);
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for Capture {
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, _: &span::Id, _: Context<'_, S>) {
+        let mut fields = Fields::default();
+        attrs.record(&mut fields);
+        self.0.lock().unwrap().push(Record {
+            span: Some(attrs.metadata().name()),
+            fields: fields.0,
+        });
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        self.0.lock().unwrap().push(Record {
+            span: ctx.event_span(event).map(|span| span.name()),
+            fields: fields.0,
+        });
+    }
+}
+
+#[test]
+fn lock_and_notify_report_name_and_location() {
+    let capture = Capture::default();
+    let monitor = Monitor::new(0).named("jobs");
+    let (lock_line, notify_line) = tracing::subscriber::with_default(
+        tracing_subscriber::registry().with(capture.clone()),
+        || {
+            let lock_line = line!() + 1;
+            let mut guard = monitor.lock();
+            let notify_line = line!() + 1;
+            assert_eq!(guard.notify_one(), 0);
+            (lock_line, notify_line)
+        },
+    );
+    let at = |line: u32| format!("{}:{line}:", file!());
+
+    let records = capture.0.lock().unwrap();
+    let [lock, locked, notified] = &records[..] else {
+        panic!("unexpected records: {records:#?}");
+    };
+
+    assert_eq!(lock.span, Some("monitor.lock"));
+    assert_eq!(lock.fields["monitor"], "jobs");
+    assert!(lock.fields["location"].starts_with(&at(lock_line)));
+
+    assert_eq!(locked.span, Some("monitor.lock"));
+    assert_eq!(locked.fields["message"], "lock attempt finished");
+    assert_eq!(locked.fields["locked"], "true");
+
+    assert_eq!(notified.span, None);
+    assert_eq!(notified.fields["message"], "monitor notified");
+    assert_eq!(notified.fields["monitor"], "jobs");
+    assert!(notified.fields["location"].starts_with(&at(notify_line)));
+    assert_eq!(notified.fields["notified"], "0");
+}